@@ -1,52 +1,104 @@
+use super::errors::Errors;
 use super::flags_type::FlagsType;
 use super::utils;
+use super::value::MAX_BITS;
 use proc_macro::*;
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum SerdeFormat {
+    Disabled,
+    Names,
+    String,
+    Numeric,
+}
+
 enum State {
     ExpectKey,
     ExpectEqual,
     ExpectValue,
     ExpectComma,
+    SkipUntilComma,
 }
 pub struct Arguments {
     pub flags_type: FlagsType,
+    // the span of the `bits` argument (if present) and `bits=auto` (use the smallest type that fits all flags)
+    pub bits_span: Option<Span>,
+    pub auto_bits: bool,
     pub none_case: String,
     pub has_empty_value: bool,
     pub disable_empty_generation: bool,
     pub debug_mode: bool,
+    pub auto_start: u32,
+    pub auto_start_span: Span,
+    pub serde_format: SerdeFormat,
+    pub serde_lenient: bool,
+    pub case_sensitive: bool,
+    pub auto_imply: bool,
+    pub display_unknown_bits: bool,
+    pub derive_debug: bool,
+    // c_header=true writes `<Name>.h` in OUT_DIR (the span is used to report I/O errors)
+    pub c_header: bool,
+    pub c_header_span: Span,
+    pub errors: Errors,
     state: State,
     key: String,
+    key_span: Span,
     value: String,
+    value_span: Span,
 }
 
 impl Arguments {
     pub fn new() -> Arguments {
         Arguments {
             flags_type: FlagsType::U32,
+            bits_span: None,
+            auto_bits: false,
             none_case: String::from("None"),
             state: State::ExpectKey,
             key: String::new(),
+            key_span: Span::call_site(),
             value: String::new(),
+            value_span: Span::call_site(),
             has_empty_value: false,
             disable_empty_generation: false,
             debug_mode: false,
+            auto_start: 0,
+            auto_start_span: Span::call_site(),
+            serde_format: SerdeFormat::Disabled,
+            serde_lenient: false,
+            case_sensitive: false,
+            auto_imply: false,
+            display_unknown_bits: false,
+            derive_debug: false,
+            c_header: false,
+            c_header_span: Span::call_site(),
+            errors: Errors::new(),
         }
     }
     fn validate_bits_attribute(&mut self) {
-        match self.value.as_str() {
-            "8" => self.flags_type = FlagsType::U8,
-            "16" => self.flags_type = FlagsType::U16,
-            "32" => self.flags_type = FlagsType::U32,
-            "64" => self.flags_type = FlagsType::U64,
-            "128" => self.flags_type = FlagsType::U128,
+        self.bits_span = Some(self.value_span);
+        if self.value == "auto" {
+            // the values are computed on 128 bits and the type is selected once all flags are known
+            self.auto_bits = true;
+            self.flags_type = FlagsType::U128;
+            return;
+        }
+        if let Some(flags_type) = FlagsType::from_str(self.value.as_str()) {
+            self.flags_type = flags_type;
+            return;
+        }
+        // more than 128 bits -> an array of u64 words
+        match self.value.parse::<u32>() {
+            Ok(bits) if (bits > 128) && (bits <= MAX_BITS) && (bits % 64 == 0) => self.flags_type = FlagsType::Words(bits / 64),
             _ => {
-                panic!("The value for `bits` attribute can be 8,16,32,64 or 128. Provided value was: {}",self.value.as_str());
+                self.errors.add(self.value_span, format!("The value for `bits` attribute can be 8,16,32,64,128, a multiple of 64 up to {} (e.g. 256 or 512), one of the types u8..u128, i8..i128, usize or isize or auto. Provided value was: {}", MAX_BITS, self.value.as_str()));
             }
         }
     }
     fn validate_empty_attribute(&mut self) {
         if !utils::validate_enum_variant_name(self.value.as_str()) {
-            panic!("Invalid name for the empty case. A valid name should contains letters, numbers or underline and must not start with a number.");
+            self.errors.add(self.value_span, String::from("Invalid name for the empty case. A valid name should contains letters, numbers or underline and must not start with a number."));
+            return;
         }
         self.none_case.clear();
         self.none_case.push_str(self.value.as_str());
@@ -69,14 +121,79 @@ impl Arguments {
         if let Some(value) = Arguments::string_to_bool(self.value.as_str()) {
             self.disable_empty_generation = value;
         } else {
-            panic!("The value for `disable_empty_generation` attribute can only be 'true' or 'false'. Provided value was: {}",self.value.as_str());
+            self.errors.add(self.value_span, format!("The value for `disable_empty_generation` attribute can only be 'true' or 'false'. Provided value was: {}",self.value.as_str()));
         }
     }
     fn validate_debug_attribute(&mut self) {
         if let Some(value) = Arguments::string_to_bool(self.value.as_str()) {
             self.debug_mode = value;
         } else {
-            panic!("The value for `debug` attribute can only be 'true' or 'false'. Provided value was: {}",self.value.as_str());
+            self.errors.add(self.value_span, format!("The value for `debug` attribute can only be 'true' or 'false'. Provided value was: {}",self.value.as_str()));
+        }
+    }
+    fn validate_auto_start_attribute(&mut self) {
+        match utils::string_to_number(self.value.as_str()) {
+            // the upper limit depends on the number of bits (checked by the parser)
+            Some(value) if value <= (u32::MAX as u128) => {
+                self.auto_start = value as u32;
+                self.auto_start_span = self.value_span;
+            }
+            _ => {
+                self.errors.add(self.value_span, format!("The value for `auto_start` attribute must be the index of a bit. Provided value was: {}",self.value.as_str()));
+            }
+        }
+    }
+    fn validate_serde_attribute(&mut self) {
+        match self.value.as_str() {
+            "names" => self.serde_format = SerdeFormat::Names,
+            "string" => self.serde_format = SerdeFormat::String,
+            "numeric" => self.serde_format = SerdeFormat::Numeric,
+            _ => {
+                self.errors.add(self.value_span, format!("The value for `serde` attribute can be 'names', 'string' or 'numeric'. Provided value was: {}",self.value.as_str()));
+            }
+        }
+    }
+    fn validate_serde_lenient_attribute(&mut self) {
+        if let Some(value) = Arguments::string_to_bool(self.value.as_str()) {
+            self.serde_lenient = value;
+        } else {
+            self.errors.add(self.value_span, format!("The value for `serde_lenient` attribute can only be 'true' or 'false'. Provided value was: {}",self.value.as_str()));
+        }
+    }
+    fn validate_case_sensitive_attribute(&mut self) {
+        if let Some(value) = Arguments::string_to_bool(self.value.as_str()) {
+            self.case_sensitive = value;
+        } else {
+            self.errors.add(self.value_span, format!("The value for `case_sensitive` attribute can only be 'true' or 'false'. Provided value was: {}",self.value.as_str()));
+        }
+    }
+    fn validate_auto_imply_attribute(&mut self) {
+        if let Some(value) = Arguments::string_to_bool(self.value.as_str()) {
+            self.auto_imply = value;
+        } else {
+            self.errors.add(self.value_span, format!("The value for `auto_imply` attribute can only be 'true' or 'false'. Provided value was: {}",self.value.as_str()));
+        }
+    }
+    fn validate_display_unknown_bits_attribute(&mut self) {
+        if let Some(value) = Arguments::string_to_bool(self.value.as_str()) {
+            self.display_unknown_bits = value;
+        } else {
+            self.errors.add(self.value_span, format!("The value for `display_unknown_bits` attribute can only be 'true' or 'false'. Provided value was: {}",self.value.as_str()));
+        }
+    }
+    fn validate_derive_debug_attribute(&mut self) {
+        if let Some(value) = Arguments::string_to_bool(self.value.as_str()) {
+            self.derive_debug = value;
+        } else {
+            self.errors.add(self.value_span, format!("The value for `derive_debug` attribute can only be 'true' or 'false'. Provided value was: {}",self.value.as_str()));
+        }
+    }
+    fn validate_c_header_attribute(&mut self) {
+        if let Some(value) = Arguments::string_to_bool(self.value.as_str()) {
+            self.c_header = value;
+            self.c_header_span = self.value_span;
+        } else {
+            self.errors.add(self.value_span, format!("The value for `c_header` attribute can only be 'true' or 'false'. Provided value was: {}",self.value.as_str()));
         }
     }
 
@@ -86,68 +203,87 @@ impl Arguments {
             "empty" => self.validate_empty_attribute(),
             "disable_empty_generation" => self.validate_noempty_attribute(),
             "debug" => self.validate_debug_attribute(),
+            "auto_start" => self.validate_auto_start_attribute(),
+            "serde" => self.validate_serde_attribute(),
+            "serde_lenient" => self.validate_serde_lenient_attribute(),
+            "case_sensitive" => self.validate_case_sensitive_attribute(),
+            "auto_imply" => self.validate_auto_imply_attribute(),
+            "c_header" => self.validate_c_header_attribute(),
+            "display_unknown_bits" => self.validate_display_unknown_bits_attribute(),
+            "derive_debug" => self.validate_derive_debug_attribute(),
             _ => {
-                panic!("Unknown attribute `{}` for EnumBitFlags. Accepted attributes are 'bits' , 'empty' and 'disable_empty_generation' !",self.key.as_str());
+                self.errors.add(self.key_span, format!("Unknown attribute `{}` for EnumBitFlags. Accepted attributes are 'bits' , 'empty' , 'disable_empty_generation' , 'debug' , 'auto_start' , 'serde' , 'serde_lenient' , 'case_sensitive' , 'auto_imply' , 'c_header' , 'display_unknown_bits' and 'derive_debug' !",self.key.as_str()));
             }
         }
     }
+    // after a syntax error, resume with the next `key=value` pair
+    fn recover(&mut self, token: &TokenTree) {
+        if let TokenTree::Punct(punctuation) = token {
+            if punctuation.as_char() == ',' {
+                self.state = State::ExpectKey;
+                return;
+            }
+        }
+        self.state = State::SkipUntilComma;
+    }
     fn validate_expect_key(&mut self, token: TokenTree) {
         if let TokenTree::Ident(ident) = token {
             self.key = ident.to_string();
+            self.key_span = ident.span();
             self.state = State::ExpectEqual;
         } else {
-            panic!(
+            self.errors.add(token.span(), format!(
                 "Expecting a key (a-zA-Z0-9) but got: `{}`",
-                token.to_string()
-            );
+                token
+            ));
+            self.recover(&token);
         }
     }
     fn validate_expect_equal(&mut self, token: TokenTree) {
-        if let TokenTree::Punct(punctuation) = token {
-            if (punctuation.as_char() != '=') && (punctuation.as_char() != ':') {
-                panic!(
-                    "Expecting asignamne ('=' or ':') symbol but got: {}",
-                    punctuation.as_char()
-                );
+        if let TokenTree::Punct(punctuation) = &token {
+            if (punctuation.as_char() == '=') || (punctuation.as_char() == ':') {
+                self.state = State::ExpectValue;
+                return;
             }
-            self.state = State::ExpectValue;
-        } else {
-            panic!(
-                "Expecting asignamne ('=' or ':') symbol but got: {}",
-                token.to_string()
-            );
         }
+        self.errors.add(token.span(), format!(
+            "Expecting asignamne ('=' or ':') symbol but got: {}",
+            token
+        ));
+        self.recover(&token);
     }
     fn validate_expect_value(&mut self, token: TokenTree) {
-        if let TokenTree::Ident(ident) = token {
+        if let TokenTree::Ident(ident) = &token {
             self.value = ident.to_string();
-            self.state = State::ExpectComma;
-        } else if let TokenTree::Literal(literal) = token {
+        } else if let TokenTree::Literal(literal) = &token {
             self.value = literal.to_string();
-            self.state = State::ExpectComma;
         } else {
-            panic!(
+            self.errors.add(token.span(), format!(
                 "Expecting a value (a-zA-Z0-9) but got: `{}`",
-                token.to_string()
-            );
+                token
+            ));
+            self.recover(&token);
+            return;
         }
+        self.value_span = token.span();
+        self.state = State::ExpectComma;
         self.validate_key_value_pair();
     }
     fn validate_expect_comma(&mut self, token: TokenTree) {
-        if let TokenTree::Punct(punctuation) = token {
-            if punctuation.as_char() != ',' {
-                panic!(
-                    "Expecting delimiter (',' comma) symbol but got: {}",
-                    punctuation.as_char()
-                );
+        if let TokenTree::Punct(punctuation) = &token {
+            if punctuation.as_char() == ',' {
+                self.state = State::ExpectKey;
+                return;
             }
-            self.state = State::ExpectKey;
-        } else {
-            panic!(
-                "Expecting delimiter (',' comma) symbol but got:{}",
-                token.to_string()
-            );
         }
+        self.errors.add(token.span(), format!(
+            "Expecting delimiter (',' comma) symbol but got: {}",
+            token
+        ));
+        self.recover(&token);
+    }
+    fn skip_until_comma(&mut self, token: TokenTree) {
+        self.recover(&token);
     }
     pub fn parse(&mut self, input: TokenStream) {
         for token in input.into_iter() {
@@ -157,7 +293,14 @@ impl Arguments {
                 State::ExpectEqual => self.validate_expect_equal(token),
                 State::ExpectValue => self.validate_expect_value(token),
                 State::ExpectComma => self.validate_expect_comma(token),
+                State::SkipUntilComma => self.skip_until_comma(token),
+            }
+        }
+        match self.state {
+            State::ExpectEqual | State::ExpectValue => {
+                self.errors.add(self.key_span, format!("Missing value for attribute `{}` (expecting `{}=<value>`)", self.key.as_str(), self.key.as_str()));
             }
+            _ => {}
         }
     }
 }
@@ -0,0 +1,108 @@
+use super::flags_type::FlagsType;
+use super::utils::to_snake_case;
+use super::value::Value;
+
+// a `#define` from the C header (the name is converted to upper snake case and prefixed with the name of the type)
+pub enum Define {
+    // a flag or a member of a group
+    Value(String, Value),
+    // the mask (`<NAME>_MASK`) and the first bit (`<NAME>_SHIFT`) of a field or group
+    Field(String, Value, u32),
+}
+
+// `IOMode` -> `IO_MODE`
+pub fn upper(name: &str) -> String {
+    return to_snake_case(name).to_uppercase();
+}
+
+// the value as a C constant of the given type; values with the sign bit set are written as negative numbers
+// for signed types (converting an out of range unsigned value to a signed type is implementation-defined)
+fn constant(flags_type: FlagsType, c_type: &str, value: Value) -> String {
+    let bits = flags_type.bits();
+    if flags_type.is_signed() && !(value & Value::bit(bits - 1)).is_zero() {
+        if value == Value::bit(bits - 1) {
+            // `int8_t` -> `INT8_MIN`
+            return c_type.trim_end_matches("_t").to_uppercase() + "_MIN";
+        }
+        let magnitude = Value::bit(bits).checked_sub(value).unwrap();
+        let suffix = if bits > 32 { "ll" } else { "" };
+        return format!("(-{}{})", magnitude, suffix);
+    }
+    let suffix = if bits > 32 { "ull" } else { "u" };
+    return format!("0x{:X}{}", value, suffix);
+}
+
+// the content of the C header for the flags type `name` (None if the type can not be represented in C)
+pub fn build(name: &str, flags_type: FlagsType, defines: &[Define]) -> Option<String> {
+    let c_type = flags_type.c_type()?;
+    let prefix = upper(name);
+    let mut header = format!("/* Generated by EnumBitFlags from `{}`. Do not edit. */\n#ifndef {}_H\n#define {}_H\n\n#include <stdint.h>\n\ntypedef {} {};\n\n", name, prefix, prefix, c_type, name);
+    for define in defines {
+        match define {
+            Define::Value(define, value) => header.push_str(format!("#define {}_{} (({}){})\n", prefix, define, name, constant(flags_type, c_type, *value)).as_str()),
+            Define::Field(define, mask, start) => {
+                header.push_str(format!("#define {}_{}_MASK (({}){})\n", prefix, define, name, constant(flags_type, c_type, *mask)).as_str());
+                header.push_str(format!("#define {}_{}_SHIFT {}\n", prefix, define, start).as_str());
+            }
+        }
+    }
+    header.push_str(format!("\n#endif /* {}_H */\n", prefix).as_str());
+    return Some(header);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defines() -> Vec<Define> {
+        return vec![
+            Define::Value(String::from("NONE"), Value::ZERO),
+            Define::Value(upper("Read"), Value::from(1)),
+            Define::Value(upper("ReadWrite"), Value::from(3)),
+            Define::Field(upper("Level"), Value::from(0x30), 4),
+            Define::Value(upper("Sign"), Value::from(0x80)),
+            Define::Value(upper("SignRead"), Value::from(0x81)),
+        ];
+    }
+
+    #[test]
+    fn unsigned_header() {
+        let header = build("IOMode", FlagsType::U8, &defines()).unwrap();
+        assert_eq!(
+            header,
+            "/* Generated by EnumBitFlags from `IOMode`. Do not edit. */\n#ifndef IO_MODE_H\n#define IO_MODE_H\n\n#include <stdint.h>\n\ntypedef uint8_t IOMode;\n\n\
+             #define IO_MODE_NONE ((IOMode)0x0u)\n\
+             #define IO_MODE_READ ((IOMode)0x1u)\n\
+             #define IO_MODE_READ_WRITE ((IOMode)0x3u)\n\
+             #define IO_MODE_LEVEL_MASK ((IOMode)0x30u)\n\
+             #define IO_MODE_LEVEL_SHIFT 4\n\
+             #define IO_MODE_SIGN ((IOMode)0x80u)\n\
+             #define IO_MODE_SIGN_READ ((IOMode)0x81u)\n\
+             \n#endif /* IO_MODE_H */\n"
+        );
+    }
+
+    #[test]
+    fn signed_values() {
+        let header = build("Flags", FlagsType::I8, &defines()).unwrap();
+        assert!(header.contains("typedef int8_t Flags;\n"));
+        assert!(header.contains("#define FLAGS_READ_WRITE ((Flags)0x3u)\n"));
+        assert!(header.contains("#define FLAGS_SIGN ((Flags)INT8_MIN)\n"));
+        assert!(header.contains("#define FLAGS_SIGN_READ ((Flags)(-127))\n"));
+        let top = vec![Define::Value(String::from("TOP"), Value::bit(63)), Define::Value(String::from("ALL"), Value::ones(64))];
+        let header = build("Flags", FlagsType::I64, &top).unwrap();
+        assert!(header.contains("#define FLAGS_TOP ((Flags)INT64_MIN)\n"));
+        assert!(header.contains("#define FLAGS_ALL ((Flags)(-1ll))\n"));
+        let header = build("Flags", FlagsType::Isize, &top).unwrap();
+        assert!(header.contains("typedef intptr_t Flags;\n"));
+        assert!(header.contains("#define FLAGS_TOP ((Flags)INTPTR_MIN)\n"));
+        let header = build("Flags", FlagsType::U64, &top).unwrap();
+        assert!(header.contains("#define FLAGS_TOP ((Flags)0x8000000000000000ull)\n"));
+    }
+
+    #[test]
+    fn unsupported_types() {
+        assert!(build("Flags", FlagsType::U128, &defines()).is_none());
+        assert!(build("Flags", FlagsType::Words(4), &defines()).is_none());
+    }
+}
@@ -0,0 +1,35 @@
+use proc_macro::*;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ConstraintKind {
+    // at most one of the flags can be set
+    Exclusive,
+    // if the first flag is set, all the other flags must be set as well
+    Requires,
+}
+
+pub struct Constraint {
+    pub kind: ConstraintKind,
+    pub span: Span,
+    pub names: Vec<Ident>,
+    // indexes of the flags (once resolved)
+    pub flags: Vec<usize>,
+}
+
+impl Constraint {
+    pub fn new(kind: ConstraintKind, span: Span) -> Constraint {
+        Constraint {
+            kind,
+            span,
+            names: Vec::new(),
+            flags: Vec::new(),
+        }
+    }
+    #[inline]
+    pub fn attribute_name(&self) -> &'static str {
+        match self.kind {
+            ConstraintKind::Exclusive => return "exclusive",
+            ConstraintKind::Requires => return "requires",
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use proc_macro::*;
+
+pub struct Errors {
+    list: Vec<(Span, String)>,
+}
+
+impl Errors {
+    pub fn new() -> Errors {
+        Errors { list: Vec::new() }
+    }
+    pub fn add(&mut self, span: Span, message: String) {
+        self.list.push((span, message));
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.list.is_empty();
+    }
+    // builds a `compile_error!("...");` invocation where every token carries the span of the offending token
+    fn compile_error(span: Span, message: &str) -> TokenStream {
+        let mut bang = Punct::new('!', Spacing::Alone);
+        bang.set_span(span);
+        let mut literal = Literal::string(message);
+        literal.set_span(span);
+        let mut group = Group::new(Delimiter::Parenthesis, TokenStream::from(TokenTree::Literal(literal)));
+        group.set_span(span);
+        let mut semicolon = Punct::new(';', Spacing::Alone);
+        semicolon.set_span(span);
+        let tokens: [TokenTree; 4] = [
+            TokenTree::Ident(Ident::new("compile_error", span)),
+            TokenTree::Punct(bang),
+            TokenTree::Group(group),
+            TokenTree::Punct(semicolon),
+        ];
+        return tokens.into_iter().collect();
+    }
+    pub fn extend_stream(&self, stream: &mut TokenStream) {
+        for (span, message) in self.list.iter() {
+            stream.extend(Errors::compile_error(*span, message.as_str()));
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use crate::flags_type::FlagsType;
+use crate::value::Value;
+use proc_macro::*;
+
+// Evaluates the value of a variant at macro time.
+// Supported syntax (same precedence as in Rust):
+//      expr    := xor ( '|' xor )*
+//      xor     := and ( '^' and )*
+//      and     := shift ( '&' shift )*
+//      shift   := sum ( ('<<' | '>>') sum )*
+//      sum     := product ( ('+' | '-') product )*
+//      product := unary ( ('*' | '/' | '%') unary )*
+//      unary   := '!' unary | '-' unary | primary
+//      primary := literal | flag name | '(' expr ')'
+// Every intermediate result must fit in the flags type.
+// For signed types the values are the bit patterns of the flags: a negative value ('-' is only allowed for
+// signed types) is stored as its two's complement and decimal literals must fit in the signed range.
+// The complement ('!') is computed relative to `mask`: the bits of all flags defined through literals for
+// composite values, or all the bits of the flags type otherwise.
+pub struct Expression<'a> {
+    tokens: Vec<TokenTree>,
+    pos: usize,
+    end_span: Span,
+    mask: Value,
+    flags_type: FlagsType,
+    resolve: &'a dyn Fn(&Ident) -> Result<Value, String>,
+}
+
+pub type EvalResult = Result<Value, (Span, String)>;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Operator {
+    Or,
+    Xor,
+    And,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl<'a> Expression<'a> {
+    pub fn new(tokens: Vec<TokenTree>, end_span: Span, mask: Value, flags_type: FlagsType, resolve: &'a dyn Fn(&Ident) -> Result<Value, String>) -> Expression<'a> {
+        Expression {
+            tokens,
+            pos: 0,
+            end_span,
+            mask,
+            flags_type,
+            resolve,
+        }
+    }
+    // returns true if the tokens reference other flags (the expression is a composite one)
+    pub fn has_names(tokens: &[TokenTree]) -> bool {
+        for token in tokens {
+            match token {
+                TokenTree::Ident(_) => {
+                    return true;
+                }
+                TokenTree::Group(group) => {
+                    let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+                    if Expression::has_names(&inner) {
+                        return true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        return false;
+    }
+    pub fn evaluate(&mut self) -> EvalResult {
+        if self.tokens.is_empty() {
+            return Err((self.end_span, String::from("Expecting a value (an integer or an expression) but got nothing")));
+        }
+        let value = self.parse_binary(Operator::Or)?;
+        if self.pos < self.tokens.len() {
+            let token = &self.tokens[self.pos];
+            return Err((token.span(), format!("Unexpected token `{}` in expression (expecting an operator or ',')", token)));
+        }
+        return Ok(value);
+    }
+    fn punct_at(&self, pos: usize) -> Option<&Punct> {
+        if let Some(TokenTree::Punct(punctuation)) = self.tokens.get(pos) {
+            return Some(punctuation);
+        }
+        return None;
+    }
+    // the binary operator at the current position and the number of tokens it uses
+    fn next_operator(&self) -> Option<(Operator, usize)> {
+        let punctuation = self.punct_at(self.pos)?;
+        let ch = punctuation.as_char();
+        if (ch == '<') || (ch == '>') {
+            if punctuation.spacing() == Spacing::Joint {
+                if let Some(second) = self.punct_at(self.pos + 1) {
+                    if second.as_char() == ch {
+                        return Some((if ch == '<' { Operator::ShiftLeft } else { Operator::ShiftRight }, 2));
+                    }
+                }
+            }
+            return None;
+        }
+        match ch {
+            '|' => return Some((Operator::Or, 1)),
+            '^' => return Some((Operator::Xor, 1)),
+            '&' => return Some((Operator::And, 1)),
+            '+' => return Some((Operator::Add, 1)),
+            '-' => return Some((Operator::Sub, 1)),
+            '*' => return Some((Operator::Mul, 1)),
+            '/' => return Some((Operator::Div, 1)),
+            '%' => return Some((Operator::Rem, 1)),
+            _ => return None,
+        }
+    }
+    // operators with the same precedence level
+    fn same_level(level: Operator, op: Operator) -> bool {
+        match level {
+            Operator::Or | Operator::Xor | Operator::And => return op == level,
+            Operator::ShiftLeft | Operator::ShiftRight => return matches!(op, Operator::ShiftLeft | Operator::ShiftRight),
+            Operator::Add | Operator::Sub => return matches!(op, Operator::Add | Operator::Sub),
+            Operator::Mul | Operator::Div | Operator::Rem => return matches!(op, Operator::Mul | Operator::Div | Operator::Rem),
+        }
+    }
+    fn parse_operand(&mut self, level: Operator) -> EvalResult {
+        match level {
+            Operator::Or => return self.parse_binary(Operator::Xor),
+            Operator::Xor => return self.parse_binary(Operator::And),
+            Operator::And => return self.parse_binary(Operator::ShiftLeft),
+            Operator::ShiftLeft | Operator::ShiftRight => return self.parse_binary(Operator::Add),
+            Operator::Add | Operator::Sub => return self.parse_binary(Operator::Mul),
+            Operator::Mul | Operator::Div | Operator::Rem => return self.parse_unary(),
+        }
+    }
+    fn parse_binary(&mut self, level: Operator) -> EvalResult {
+        let mut value = self.parse_operand(level)?;
+        while let Some((op, count)) = self.next_operator() {
+            if !Expression::same_level(level, op) {
+                break;
+            }
+            let span = self.tokens[self.pos].span();
+            self.pos += count;
+            let right = self.parse_operand(level)?;
+            value = self.apply(op, value, right, span)?;
+        }
+        return Ok(value);
+    }
+    fn overflow(&self, span: Span) -> (Span, String) {
+        return (span, format!("The result of the expression does not fit in the {} bits used to store the flags (0x{:X} is the maximum value allowed). Change the representation by using the attribute bits or change the value !", self.flags_type.bits(), self.flags_type.max_value()));
+    }
+    fn apply(&self, op: Operator, left: Value, right: Value, span: Span) -> EvalResult {
+        let result = match op {
+            Operator::Or => Some(left | right),
+            Operator::Xor => Some(left ^ right),
+            Operator::And => Some(left & right),
+            Operator::ShiftLeft | Operator::ShiftRight => {
+                if right >= Value::from(self.flags_type.bits() as u128) {
+                    return Err((span, format!("Shift amount ({}) must be smaller than the number of bits used to store the flags ({})", right, self.flags_type.bits())));
+                }
+                let right = right.as_u128().unwrap() as u32;
+                if op == Operator::ShiftLeft {
+                    let value = left << right;
+                    // bits shifted out of the flags type
+                    if (value >> right) != left { None } else { Some(value) }
+                } else {
+                    Some(left >> right)
+                }
+            }
+            Operator::Add => left.checked_add(right),
+            Operator::Sub => {
+                if right > left {
+                    return Err((span, format!("The result of the subtraction is negative ({} - {})", left, right)));
+                }
+                left.checked_sub(right)
+            }
+            Operator::Mul => left.checked_mul(right),
+            Operator::Div | Operator::Rem => {
+                match left.div_rem(right) {
+                    Some((quotient, remainder)) => if op == Operator::Div { Some(quotient) } else { Some(remainder) },
+                    None => return Err((span, String::from("Division by zero"))),
+                }
+            }
+        };
+        match result {
+            Some(value) if value <= self.flags_type.max_value() => return Ok(value),
+            _ => return Err(self.overflow(span)),
+        }
+    }
+    fn parse_unary(&mut self) -> EvalResult {
+        if let Some(punctuation) = self.punct_at(self.pos) {
+            if punctuation.as_char() == '!' {
+                self.pos += 1;
+                let value = self.parse_unary()?;
+                return Ok((!value) & self.mask);
+            }
+            if punctuation.as_char() == '-' {
+                if !self.flags_type.is_signed() {
+                    return Err((punctuation.span(), format!("Negative values can only be used with signed types (i8..i128 or isize) but the flags are stored as {}. Change the representation by using the attribute bits or change the value !", self.flags_type.as_str())));
+                }
+                self.pos += 1;
+                let value = self.parse_unary()?;
+                return Ok(value.wrapping_neg(self.flags_type.max_value()));
+            }
+        }
+        return self.parse_primary();
+    }
+    fn is_decimal(&self, literal: &Literal) -> bool {
+        let text = literal.to_string();
+        return !(text.starts_with("0x") || text.starts_with("0b") || text.starts_with("0o"));
+    }
+    // true if the current token follows an unary '-'
+    fn is_negated(&self) -> bool {
+        if (self.pos < 2) || !matches!(self.punct_at(self.pos - 2), Some(p) if p.as_char() == '-') {
+            return false;
+        }
+        // a '-' that follows a value is the subtraction operator
+        return (self.pos < 3) || matches!(self.tokens.get(self.pos - 3), Some(TokenTree::Punct(_)));
+    }
+    fn parse_primary(&mut self) -> EvalResult {
+        let token = match self.tokens.get(self.pos) {
+            Some(token) => token.clone(),
+            None => {
+                return Err((self.end_span, String::from("Unexpected end of expression (expecting a value, a flag name or '(')")));
+            }
+        };
+        self.pos += 1;
+        match &token {
+            TokenTree::Literal(literal) => {
+                if let Some(value) = super::utils::string_to_number(literal.to_string().as_str()) {
+                    let value = Value::from(value);
+                    if self.flags_type.is_signed() && self.is_decimal(literal) {
+                        // the largest positive value (or the smallest negative one if the literal follows a '-')
+                        let mut limit = Value::bit(self.flags_type.bits() - 1);
+                        if !self.is_negated() {
+                            limit = limit.checked_sub(Value::from(1)).unwrap();
+                        }
+                        if value > limit {
+                            let bits = self.flags_type.bits();
+                            return Err((literal.span(), format!("The value {} is out of range for {} (decimal values must be between -{} and {}). Use a negative value or an hexadecimal/binary value to set the sign bit !", literal, self.flags_type.as_str(), Value::bit(bits - 1), Value::ones(bits - 1))));
+                        }
+                    }
+                    if value > self.flags_type.max_value() {
+                        let bits = self.flags_type.bits();
+                        return Err((literal.span(), format!("Enum is set to store data on {} bits. The value {} is larger than the 0x{:X} (the maximum value allowed for an {} bit value). Change the representation by using the attribute bits or change the value !", bits, literal, self.flags_type.max_value(), bits)));
+                    }
+                    return Ok(value);
+                }
+                if literal.to_string().starts_with(|ch: char| ch.is_ascii_digit()) {
+                    return Err((literal.span(), format!("The value {} does not fit in 128 bits (use an expression, e.g. `1 << 200`, for larger values)", literal)));
+                }
+                return Err((literal.span(), format!("Expecting an integer value (but got: {})", literal)));
+            }
+            TokenTree::Ident(ident) => {
+                return (self.resolve)(ident).map_err(|message| (ident.span(), message));
+            }
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Parenthesis => {
+                let mut inner = Expression::new(group.stream().into_iter().collect(), group.span_close(), self.mask, self.flags_type, self.resolve);
+                return inner.evaluate();
+            }
+            _ => {
+                return Err((token.span(), format!("Expecting a value, a flag name or '(' but got: {}", token)));
+            }
+        }
+    }
+}
@@ -1,21 +1,108 @@
+use super::value::Value;
+
 #[derive(Clone,Copy,PartialEq)]
-#[repr(u8)]
 pub enum FlagsType {
     U8,
     U16,
     U32,
     U64,
     U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Usize,
+    Isize,
+    // more than 128 bits, stored in an array of u64 words (the number of words)
+    Words(u32),
 }
 
 impl FlagsType {
+    pub fn from_str(name: &str) -> Option<FlagsType> {
+        match name {
+            "8" | "u8" => return Some(FlagsType::U8),
+            "16" | "u16" => return Some(FlagsType::U16),
+            "32" | "u32" => return Some(FlagsType::U32),
+            "64" | "u64" => return Some(FlagsType::U64),
+            "128" | "u128" => return Some(FlagsType::U128),
+            "i8" => return Some(FlagsType::I8),
+            "i16" => return Some(FlagsType::I16),
+            "i32" => return Some(FlagsType::I32),
+            "i64" => return Some(FlagsType::I64),
+            "i128" => return Some(FlagsType::I128),
+            "usize" => return Some(FlagsType::Usize),
+            "isize" => return Some(FlagsType::Isize),
+            _ => return None,
+        }
+    }
     pub fn as_str(&self) -> &'static str {
         match self {
             FlagsType::U8 => return "u8",
             FlagsType::U16 => return "u16",
             FlagsType::U32 => return "u32",
             FlagsType::U64 => return "u64",
-            FlagsType::U128 => return "u128"
+            FlagsType::U128 => return "u128",
+            FlagsType::I8 => return "i8",
+            FlagsType::I16 => return "i16",
+            FlagsType::I32 => return "i32",
+            FlagsType::I64 => return "i64",
+            FlagsType::I128 => return "i128",
+            FlagsType::Usize => return "usize",
+            FlagsType::Isize => return "isize",
+            // a type generated next to the flags type (see Parser::add_wide_value)
+            FlagsType::Words(_) => return "$$(NAME)$$Value"
+        }
+    }
+    // the unsigned type with the same number of bits (used to write the literals of signed types)
+    pub fn unsigned(&self) -> FlagsType {
+        match self {
+            FlagsType::I8 => return FlagsType::U8,
+            FlagsType::I16 => return FlagsType::U16,
+            FlagsType::I32 => return FlagsType::U32,
+            FlagsType::I64 => return FlagsType::U64,
+            FlagsType::I128 => return FlagsType::U128,
+            FlagsType::Isize => return FlagsType::Usize,
+            _ => return *self,
+        }
+    }
+    // usize and isize are considered to have 64 bits (the compiler reports values that do not fit
+    // on targets with a smaller pointer width)
+    pub fn bits(&self) -> u32 {
+        match self {
+            FlagsType::U8 | FlagsType::I8 => return 8,
+            FlagsType::U16 | FlagsType::I16 => return 16,
+            FlagsType::U32 | FlagsType::I32 => return 32,
+            FlagsType::U64 | FlagsType::I64 | FlagsType::Usize | FlagsType::Isize => return 64,
+            FlagsType::U128 | FlagsType::I128 => return 128,
+            FlagsType::Words(words) => return words * 64
+        }
+    }
+    // the type from <stdint.h> used in the C header (C has no standard 128 bit type)
+    pub fn c_type(&self) -> Option<&'static str> {
+        match self {
+            FlagsType::U8 => return Some("uint8_t"),
+            FlagsType::U16 => return Some("uint16_t"),
+            FlagsType::U32 => return Some("uint32_t"),
+            FlagsType::U64 => return Some("uint64_t"),
+            FlagsType::I8 => return Some("int8_t"),
+            FlagsType::I16 => return Some("int16_t"),
+            FlagsType::I32 => return Some("int32_t"),
+            FlagsType::I64 => return Some("int64_t"),
+            FlagsType::Usize => return Some("uintptr_t"),
+            FlagsType::Isize => return Some("intptr_t"),
+            _ => return None,
         }
     }
-}
\ No newline at end of file
+    #[inline]
+    pub fn is_signed(&self) -> bool {
+        return self.unsigned() != *self;
+    }
+    #[inline]
+    pub fn is_wide(&self) -> bool {
+        return matches!(self, FlagsType::Words(_));
+    }
+    pub fn max_value(&self) -> Value {
+        return Value::ones(self.bits());
+    }
+}
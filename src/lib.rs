@@ -1,6 +1,12 @@
 mod parser;
 mod arguments;
+mod errors;
+mod expression;
+mod variant;
+mod constraint;
+mod c_header;
 mod flags_type;
+mod value;
 mod utils;
 
 use proc_macro::*;
@@ -16,8 +22,18 @@ pub fn EnumBitFlags(args: TokenStream, input: TokenStream) -> TokenStream {
     a.parse(args);
     let mut p = Parser::new(a);
     p.parse(input);
+    p.write_c_header();
+    if p.has_errors() {
+        return p.errors_stream();
+    }
     p.add_methods();
+    p.add_groups();
+    p.add_wide_value();
+    p.add_validation();
     p.add_operators();
+    p.add_iterators();
+    p.add_from_str();
+    p.add_serde();
     p.replace_template_parameters();
     return p.stream();    
 }
@@ -1,54 +1,239 @@
-use crate::flags_type::FlagsType;
 use proc_macro::*;
 
 use super::arguments::*;
+use super::errors::Errors;
+use super::value::Value;
+use super::flags_type::FlagsType;
+use super::expression::Expression;
+use super::variant::Variant;
+use super::constraint::{Constraint, ConstraintKind};
+use super::c_header::{self, Define};
 use std::collections::HashMap;
 use std::str::FromStr;
-use std::u64;
 
 enum State {
     ExpectVisibility,
+    ExpectAttribute,
     ExpectVisibilityGroup,
     ExpectEnum,
     ExpectName,
     ExpectOpenBrace,
     ExpectFlag,
+    ExpectFlagAttribute,
     ExpectEqual,
     ExpectValue,
-    ExpectComma,
+    SkipUntilComma,
+    Stop,
 }
 
+// methods generated for every flags type (neither a flag nor a field getter can use one of these names)
+const GENERATED_METHODS: &[&str] = &[
+    "from_value", "from_value_truncate", "from_value_retain", "from_value_unchecked", "contains", "contains_one",
+    "is_empty", "clear", "remove", "set", "union", "intersection", "difference", "symmetric_difference",
+    "complement", "get_value", "all", "is_all", "count", "known_bits", "unknown_bits", "has_unknown_bits", "len",
+    "iter", "iter_names", "parse_names", "expand", "normalize", "validate", "try_from_value", "is_subset_of",
+    "is_superset_of", "is_disjoint", "or", "and", "xor", "not",
+];
+// suffixes of the types generated next to the flags type (`<Name>Iter`, `<Name>ParseError`, ...)
+const GENERATED_TYPE_SUFFIXES: &[&str] = &["Iter", "IterNames", "ParseError", "ViolationError"];
+
 pub struct Parser {
     output: String,
     name: String,
     state: State,
     args: Arguments,
-    last_flag: String,
     visibility: String,
-    last_flag_hash: u64,
-    map_values: HashMap<u128, String>,
-    map_names: HashMap<u64, u128>,
+    attributes: String,
+    derives: Vec<String>,
+    last_flag_attributes: String,
+    last_flag_cfg: Vec<String>,
+    last_flag_cfg_span: Option<Span>,
+    last_flag_implies: Vec<Ident>,
+    variants: Vec<Variant>,
+    constraints: Vec<Constraint>,
+    map_values: HashMap<Value, String>,
+    map_names: HashMap<String, usize>,
+    // the names of the variants dropped because of a syntax error
+    invalid_names: Vec<String>,
     has_empty_value: bool,
-    all_set_bits: u128,
+    has_repr: bool,
+    all_set_bits: Value,
+    field_bits: Value,
+    flags_count: usize,
+    errors: Errors,
 }
 
 impl Parser {
     pub fn new(arguments: Arguments) -> Parser {
+        // Debug is generated (see add_operators) unless `derive_debug` is set
+        let derives = ["Copy", "Clone", "Debug", "PartialEq", "Eq", "Hash", "PartialOrd", "Ord"]
+            .iter()
+            .filter(|name| arguments.derive_debug || (**name != "Debug"))
+            .map(|name| String::from(*name))
+            .collect();
         Parser {
             output: String::with_capacity(1024),
             name: String::new(),
             visibility: String::new(),
+            attributes: String::new(),
+            derives,
+            last_flag_attributes: String::new(),
+            last_flag_cfg: Vec::new(),
+            last_flag_cfg_span: None,
+            last_flag_implies: Vec::new(),
             state: State::ExpectVisibility,
             args: arguments,
-            last_flag: String::new(),
-            last_flag_hash: 0,
+            variants: Vec::with_capacity(8),
+            constraints: Vec::new(),
             map_values: HashMap::with_capacity(8),
             map_names: HashMap::with_capacity(8),
+            invalid_names: Vec::new(),
             has_empty_value: false,
-            all_set_bits: 0, 
+            has_repr: false,
+            all_set_bits: Value::ZERO, 
+            field_bits: Value::ZERO,
+            flags_count: 0,
+            errors: Errors::new(),
+        }
+    }
+    // stops parsing the enum (used for errors where there is no sensible way to continue)
+    fn stop(&mut self, span: Span, message: String) {
+        self.errors.add(span, message);
+        self.state = State::Stop;
+    }
+    // reports an error for the current variant and resumes with the next one
+    fn recover(&mut self, token: &TokenTree, message: String) {
+        self.errors.add(token.span(), message);
+        if let TokenTree::Punct(punctuation) = token {
+            if punctuation.as_char() == ',' {
+                self.state = State::ExpectFlag;
+                return;
+            }
+        }
+        self.state = State::SkipUntilComma;
+    }
+    // the name of a trait without its path (`std::hash::Hash` -> `Hash`)
+    fn trait_name(path: &str) -> &str {
+        return path.rsplit("::").next().unwrap_or(path);
+    }
+    // traits that are implemented manually for the generated struct (a derive for them would conflict)
+    fn is_implemented_trait(&self, name: &str) -> bool {
+        match Parser::trait_name(name) {
+            "Default" => return true,
+            "Debug" => return !self.args.derive_debug,
+            _ => return false,
+        }
+    }
+    fn add_derives(&mut self, group: &Group) {
+        let mut path = String::new();
+        for token in group.stream().into_iter().chain(std::iter::once(TokenTree::Punct(Punct::new(',', Spacing::Alone)))) {
+            if let TokenTree::Punct(punctuation) = &token {
+                if punctuation.as_char() == ',' {
+                    // the traits derived by default (e.g. `Hash`) can also be requested explicitly
+                    let derived = self.derives.iter().any(|name| Parser::trait_name(name) == Parser::trait_name(&path));
+                    if (!path.is_empty()) && (!self.is_implemented_trait(&path)) && (!derived) {
+                        self.derives.push(path.clone());
+                    }
+                    path.clear();
+                    continue;
+                }
+            }
+            path.push_str(token.to_string().as_str());
+        }
+    }
+    fn validate_expect_attribute(&mut self, token: TokenTree) {
+        if let TokenTree::Group(group) = &token {
+            if group.delimiter() == Delimiter::Bracket {
+                let mut iter = group.stream().into_iter();
+                if let (Some(TokenTree::Ident(ident)), Some(TokenTree::Group(derives)), None) = (iter.next(), iter.next(), iter.next()) {
+                    if (ident.to_string() == "derive") && (derives.delimiter() == Delimiter::Parenthesis) {
+                        self.add_derives(&derives);
+                        self.state = State::ExpectVisibility;
+                        return;
+                    }
+                    let kind = match ident.to_string().as_str() {
+                        "exclusive" => Some(ConstraintKind::Exclusive),
+                        "requires" => Some(ConstraintKind::Requires),
+                        _ => None,
+                    };
+                    if let Some(kind) = kind {
+                        self.add_constraint(kind, ident.span(), &derives);
+                        self.state = State::ExpectVisibility;
+                        return;
+                    }
+                    if (ident.to_string() == "repr") && (derives.delimiter() == Delimiter::Parenthesis) {
+                        self.add_repr(&derives);
+                        self.state = State::ExpectVisibility;
+                        return;
+                    }
+                }
+                self.attributes.push('#');
+                self.attributes.push_str(group.to_string().as_str());
+                self.attributes.push('\n');
+                self.state = State::ExpectVisibility;
+                return;
+            }
+        }
+        self.stop(token.span(), format!("Expecting an attribute (for example: #[derive(...)], #[doc = \"...\"], ...) but got: {}", token));
+    }
+    // #[repr(u16)] selects the type used to store the flags. The struct is always `#[repr(transparent)]`
+    // (layout-identical to its value), so `C` and `transparent` are accepted and other hints are rejected.
+    fn add_repr(&mut self, hints: &Group) {
+        let mut tokens = hints.stream().into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            match &token {
+                TokenTree::Ident(ident) if ident.to_string().starts_with(['u', 'i']) && FlagsType::from_str(ident.to_string().as_str()).is_some() => {
+                    let flags_type = FlagsType::from_str(ident.to_string().as_str()).unwrap();
+                    if self.args.bits_span.is_some() && (self.args.auto_bits || (self.args.flags_type != flags_type)) {
+                        let bits = if self.args.auto_bits { "auto" } else { self.args.flags_type.as_str() };
+                        self.errors.add(ident.span(), format!("The representation from #[repr({})] conflicts with the `bits` argument ({}). Remove one of them or use the same type !", ident, bits));
+                    } else if self.has_repr && (self.args.flags_type != flags_type) {
+                        self.errors.add(ident.span(), format!("Conflicting representation hints: #[repr({})] and #[repr({})]", self.args.flags_type.as_str(), ident));
+                    } else {
+                        self.args.flags_type = flags_type;
+                        self.has_repr = true;
+                    }
+                }
+                TokenTree::Ident(ident) if (ident.to_string() == "C") || (ident.to_string() == "transparent") => {}
+                TokenTree::Punct(p) if p.as_char() == ',' => {}
+                _ => {
+                    // hints with arguments, e.g. `align(4)`, are reported once
+                    let mut hint = token.to_string();
+                    if let Some(TokenTree::Group(arguments)) = tokens.peek() {
+                        hint.push_str(arguments.to_string().as_str());
+                        tokens.next();
+                    }
+                    self.errors.add(token.span(), format!("Unsupported representation hint: {} (the generated struct is always #[repr(transparent)], with the same layout as its value)", hint));
+                }
+            }
         }
     }
+    // #[exclusive(A, B, ...)] or #[requires(A, B, ...)]
+    fn add_constraint(&mut self, kind: ConstraintKind, span: Span, list: &Group) {
+        let mut constraint = Constraint::new(kind, span);
+        for token in list.stream() {
+            match &token {
+                TokenTree::Ident(ident) => constraint.names.push(ident.clone()),
+                TokenTree::Punct(p) if p.as_char() == ',' => {}
+                _ => {
+                    self.errors.add(token.span(), format!("Expecting a list of flag names for the `{}` attribute (for example: #[{}(A, B)]) but got: {}", constraint.attribute_name(), constraint.attribute_name(), token));
+                    return;
+                }
+            }
+        }
+        if constraint.names.len() < 2 {
+            self.errors.add(list.span(), format!("The `{}` attribute requires at least two flags (for example: #[{}(A, B)])", constraint.attribute_name(), constraint.attribute_name()));
+            return;
+        }
+        self.constraints.push(constraint);
+    }
     fn validate_expect_visibility(&mut self, token: TokenTree) {
+        if let TokenTree::Punct(punctuation) = &token {
+            if punctuation.as_char() == '#' {
+                self.state = State::ExpectAttribute;
+                return;
+            }
+        }
         if let TokenTree::Ident(ident) = token.clone() {
             let txt = ident.to_string();
             if txt == "pub" {
@@ -58,7 +243,7 @@ impl Parser {
                 self.validate_expect_enum(token);
             }
         } else {
-            panic!("Expecting an enum or pub keyword but got: {:?}", token);
+            self.stop(token.span(), format!("Expecting an enum or pub keyword but got: {}", token));
         }
     }    
     fn validate_expect_visibility_group(&mut self, token: TokenTree) {
@@ -68,14 +253,14 @@ impl Parser {
                 self.state = State::ExpectEnum;                
             }
             else {
-                panic!("Expecting a visibility group (for example: (crate), (super), ...) but got: {:?}", token);
+                self.stop(token.span(), format!("Expecting a visibility group (for example: (crate), (super), ...) but got: {}", token));
             }            
         } else {
             self.validate_expect_enum(token);
         }
     }    
     fn validate_expect_enum(&mut self, token: TokenTree) {
-        if let TokenTree::Ident(ident) = token {
+        if let TokenTree::Ident(ident) = &token {
             let txt = ident.to_string();
             if txt == "pub" {
                 self.state = State::ExpectEnum;                
@@ -83,11 +268,14 @@ impl Parser {
                 return;
             }
             if txt != "enum" {
-                panic!("Expecting an enum keywork but got: {}", txt);
+                self.stop(ident.span(), format!("Expecting an enum keywork but got: {}", txt));
+                return;
             }
+            self.output.push_str(self.attributes.as_str());
+            self.output.push_str(format!("#[derive({})]", self.derives.join(",")).as_str());
             self.output.push_str(
                 r#"
-            #[derive(Copy,Clone,Debug)]
+            #[repr(transparent)]
             $$(VISIBILITY)$$ struct $$(NAME)$$ { 
                 value: $$(BITS)$$ 
             }
@@ -96,7 +284,7 @@ impl Parser {
             );
             self.state = State::ExpectName;
         } else {
-            panic!("Expecting an enum keyword but got: {:?}", token);
+            self.stop(token.span(), format!("Expecting an enum keyword but got: {}", token));
         }
     }
     fn validate_expect_enum_name(&mut self, token: TokenTree) {
@@ -104,135 +292,879 @@ impl Parser {
             self.name = ident.to_string();
             self.state = State::ExpectOpenBrace;
         } else {
-            panic!("Expecting the name of the enum but got: {:?}", token);
+            self.stop(token.span(), format!("Expecting the name of the enum but got: {}", token));
         }
     }
     fn validate_expect_open_brace(&mut self, token: TokenTree) {
-        if let TokenTree::Group(group) = token {
-            if group.delimiter() != Delimiter::Brace {
-                panic!(
-                    "Expecting an open brace '{{' after enum name but got {:?}",
-                    group.delimiter()
-                );
-            }
-            self.state = State::ExpectFlag;
-            self.parse(group.stream());
-        } else {
-            panic!(
-                "Expecting an open brace '{{' after enum name but got {:?}",
-                token
-            );
+        if let TokenTree::Group(group) = &token {
+            if group.delimiter() == Delimiter::Brace {
+                self.state = State::ExpectFlag;
+                self.parse(group.stream());
+                if let State::ExpectEqual = self.state {
+                    self.variants.last_mut().unwrap().auto = true;
+                }
+                self.state = State::Stop;
+                self.compute_values();
+                if self.errors.is_empty() {
+                    if self.args.auto_bits {
+                        self.select_auto_bits();
+                    }
+                    self.compute_implications();
+                    self.compute_constraints();
+                }
+                return;
+            }
+        }
+        self.stop(token.span(), format!(
+            "Expecting an open brace '{{' after enum name but got {}",
+            token
+        ));
+    }
+    fn validate_expect_flag_attribute(&mut self, token: TokenTree) {
+        if let TokenTree::Group(group) = &token {
+            if group.delimiter() == Delimiter::Bracket {
+                let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+                if let [TokenTree::Ident(ident), TokenTree::Group(list)] = inner.as_slice() {
+                    if ident.to_string() == "implies" {
+                        self.add_implied_flags(list);
+                        self.state = State::ExpectFlag;
+                        return;
+                    }
+                    // `#[cfg(...)]` is kept on the associated constant, and the generated tables (names, masks, count)
+                    // use the same predicate (through the `cfg!` macro)
+                    if (ident.to_string() == "cfg") && (list.delimiter() == Delimiter::Parenthesis) {
+                        self.last_flag_cfg.push(list.stream().to_string());
+                        self.last_flag_cfg_span.get_or_insert(ident.span());
+                    }
+                }
+                self.last_flag_attributes.push('#');
+                self.last_flag_attributes.push_str(group.to_string().as_str());
+                self.last_flag_attributes.push('\n');
+                self.state = State::ExpectFlag;
+                return;
+            }
+        }
+        self.recover(&token, format!("Expecting an attribute (for example: #[doc = \"...\"], #[cfg(...)], ...) but got: {}", token));
+    }
+    // #[implies(A, B, ...)] or #[implies(A | B | ...)]
+    fn add_implied_flags(&mut self, list: &Group) {
+        for token in list.stream() {
+            match &token {
+                TokenTree::Ident(ident) => self.last_flag_implies.push(ident.clone()),
+                TokenTree::Punct(p) if (p.as_char() == ',') || (p.as_char() == '|') => {}
+                _ => {
+                    self.errors.add(token.span(), format!("Expecting a list of flag names for the `implies` attribute (for example: #[implies(Read, Write)]) but got: {}", token));
+                    return;
+                }
+            }
         }
     }
     fn validate_expect_flag(&mut self, token: TokenTree) {
-        if let TokenTree::Ident(ident) = token {
-            self.last_flag = ident.to_string();
-            self.last_flag_hash = super::utils::compute_string_hash(self.last_flag.as_bytes());
-            if self.map_names.contains_key(&self.last_flag_hash) {
-                panic!("Flag {} is used twice in the enum (keep in mind that case is not checked -> \"AB\" and \"ab\" are considered the same variant",self.last_flag.as_str());
+        if let TokenTree::Punct(punctuation) = &token {
+            if punctuation.as_char() == '#' {
+                self.state = State::ExpectFlagAttribute;
+                return;
             }
-            self.output.push_str("\t$$(VISIBILITY)$$ const ");
-            self.output.push_str(self.last_flag.as_str());
-            self.output.push_str(": $$(NAME)$$ = $$(NAME)$$ { value: ");
+        }
+        if let TokenTree::Ident(ident) = &token {
+            let name = ident.to_string();
+            let key = self.name_key(name.as_str());
+            if let Some(index) = self.map_names.get(&key) {
+                if self.args.case_sensitive || (self.variants[*index].name == name) {
+                    self.errors.add(ident.span(), format!("Flag {} is used twice in the enum", name.as_str()));
+                } else {
+                    self.errors.add(ident.span(), format!("Flag {} is used twice in the enum (keep in mind that case is not checked -> \"{}\" and \"{}\" are considered the same variant unless the attribute 'case_sensitive' is set)", name.as_str(), self.variants[*index].name.as_str(), name.as_str()));
+                }
+            } else {
+                self.map_names.insert(key, self.variants.len());
+            }
+            // the flags are associated constants, so they share the namespace of the generated methods
+            if GENERATED_METHODS.contains(&name.as_str()) {
+                self.errors.add(ident.span(), format!("The name of flag `{}` conflicts with the generated method `{}`", name.as_str(), name.as_str()));
+            } else if name == "ALL_BITS" {
+                self.errors.add(ident.span(), String::from("The name of flag `ALL_BITS` conflicts with the generated constant `ALL_BITS`"));
+            }
+            let attributes = std::mem::take(&mut self.last_flag_attributes);
+            self.variants.push(Variant::new(name, ident.span(), attributes));
+            self.variants.last_mut().unwrap().implies = std::mem::take(&mut self.last_flag_implies);
+            self.variants.last_mut().unwrap().cfg = std::mem::take(&mut self.last_flag_cfg);
+            self.variants.last_mut().unwrap().cfg_span = self.last_flag_cfg_span.take();
             self.state = State::ExpectEqual;
         } else {
-            panic!("Expecting the name of a flag but got: {:?}", token);
+            self.recover(&token, format!("Expecting the name of a flag but got: {}", token));
         }
     }
     fn validate_expect_equal(&mut self, token: TokenTree) {
-        if let TokenTree::Punct(punctuation) = token {
-            if punctuation.as_char() != '=' {
-                panic!(
-                    "Expecting equal '=' symbol but got: {}",
-                    punctuation.as_char()
-                );
+        if let TokenTree::Punct(punctuation) = &token {
+            if punctuation.as_char() == '=' {
+                self.variants.last_mut().unwrap().value_span = punctuation.span();
+                self.state = State::ExpectValue;
+                return;
+            }
+            if punctuation.as_char() == ',' {
+                // no value --> the next free bit will be used
+                self.variants.last_mut().unwrap().auto = true;
+                self.state = State::ExpectFlag;
+                return;
             }
-            self.state = State::ExpectValue;
-        } else {
-            panic!("Expecting equal '=' symbol but got: {:?}", token);
         }
+        // the variant is dropped (its name must not be found by the flags that refer to it)
+        let variant = self.variants.pop().unwrap();
+        let key = self.name_key(variant.name.as_str());
+        if self.map_names.get(&key) == Some(&self.variants.len()) {
+            self.map_names.remove(&key);
+        }
+        self.invalid_names.push(variant.name);
+        self.recover(&token, format!("Expecting equal '=' symbol but got: {}", token));
     }
     fn validate_expect_value(&mut self, token: TokenTree) {
-        if let TokenTree::Literal(l) = token {
-            let value = super::utils::string_to_number(l.to_string().as_str());
-            if value.is_none() {
-                panic!("Expecting an integer value (but got: {})", l.to_string());
-            }
-            let value = value.unwrap();
-            if (value > 0xFF) && (self.args.flags_type == FlagsType::U8) {
-                panic!("Enum is set to store data on 8 bits. The value {} is larger than the 0xFF (the maximum value allowed for an 8 bit value). Change the representation by using the attribute bits or change the value !",l.to_string());
-            }
-            if (value > 0xFFFF) && (self.args.flags_type == FlagsType::U16) {
-                panic!("Enum is set to store data on 16 bits. The value {} is larger than the 0xFFFF (the maximum value allowed for an 16 bit value). Change the representation by using the attribute bits or change the value !",l.to_string());
-            }
-            if (value > 0xFFFFFFFF) && (self.args.flags_type == FlagsType::U32) {
-                panic!("Enum is set to store data on 32 bits. The value {} is larger than the 0xFFFFFFFF (the maximum value allowed for an 32 bit value). Change the representation by using the attribute bits or change the value !",l.to_string());
-            }
-            if (value > 0xFFFFFFFFFFFFFFFF) && (self.args.flags_type == FlagsType::U64) {
-                panic!("Enum is set to store data on 64 bits. The value {} is larger than the 0xFFFFFFFFFFFFFFFF (the maximum value allowed for an 64 bit value). Change the representation by using the attribute bits or change the value !",l.to_string());
-            }
-            if self.map_values.contains_key(&value) {
-                panic!(
-                    "Flag {} and {} have the same value !",
-                    self.map_values.get(&value).unwrap(),
-                    self.last_flag.as_str()
-                );
-            }
-            // check for None/Empty value
-            if value == 0 {
-                if self.args.disable_empty_generation {
-                    panic!("You have disabled empty variant generation. As such, no variant with value 0 is possible. Remove the flag `{}` or remove the attribute 'disable_empty_generation'", self.last_flag.as_str());
-                }
-                if self.args.has_empty_value {
-                    panic!("You have already specified a variant for cases where no bits are set in the arguments: '{}'. Either remove variant '{}' or remove the argument 'empty={}'", self.args.none_case.as_str(), self.last_flag.as_str(),self.args.none_case.as_str());
-                }
-                // all good --> mark has_empty_value so that we don't add one by default
-                self.has_empty_value = true;
-                self.args.none_case.clear();
-                self.args.none_case.push_str(&self.last_flag);
-            }                        
-            self.map_values.insert(value, self.last_flag.clone());
-            self.map_names.insert(self.last_flag_hash, value);
-            self.output
-                .push_str(&format!("0x{:X}{}", value, self.args.flags_type.as_str()));
-            self.output.push_str(" };\n");
-            self.state = State::ExpectComma;
-            self.all_set_bits |= value;
-        } else {
-            panic!("Expecting the name of a flag but got: {:?}", token);
+        if let TokenTree::Punct(punctuation) = &token {
+            if punctuation.as_char() == ',' {
+                self.state = State::ExpectFlag;
+                return;
+            }
         }
+        self.variants.last_mut().unwrap().tokens.push(token);
     }
-    fn validate_expect_comma(&mut self, token: TokenTree) {
-        if let TokenTree::Punct(punctuation) = token {
-            if punctuation.as_char() != ',' {
-                panic!("Expecting ',' separator but got: {}", punctuation.as_char());
+    fn skip_until_comma(&mut self, token: TokenTree) {
+        if let TokenTree::Punct(punctuation) = &token {
+            if punctuation.as_char() == ',' {
+                self.state = State::ExpectFlag;
+            }
+        }
+    }
+    fn validate_flag_value(&mut self, index: usize) {
+        let value = self.variants[index].value;
+        let span = self.variants[index].value_span();
+        let name = self.variants[index].name.clone();
+        if !(value & self.field_bits).is_zero() {
+            self.errors.add(span, format!("Flag {} uses bits that belong to a field !", name.as_str()));
+            return;
+        }
+        if self.map_values.contains_key(&value) {
+            self.errors.add(span, format!(
+                "Flag {} and {} have the same value !",
+                self.map_values.get(&value).unwrap(),
+                name.as_str()
+            ));
+            return;
+        }
+        // check for None/Empty value
+        if value.is_zero() {
+            if self.args.disable_empty_generation {
+                self.errors.add(span, format!("You have disabled empty variant generation. As such, no variant with value 0 is possible. Remove the flag `{}` or remove the attribute 'disable_empty_generation'", name.as_str()));
+                return;
+            }
+            if self.args.has_empty_value {
+                self.errors.add(span, format!("You have already specified a variant for cases where no bits are set in the arguments: '{}'. Either remove variant '{}' or remove the argument 'empty={}'", self.args.none_case.as_str(), name.as_str(),self.args.none_case.as_str()));
+                return;
+            }
+            // all good --> mark has_empty_value so that we don't add one by default
+            self.has_empty_value = true;
+            self.args.none_case.clear();
+            self.args.none_case.push_str(&name);
+        }
+        self.map_values.insert(value, name);
+        self.all_set_bits |= value;
+    }
+    fn validate_composite_value(&mut self, index: usize) {
+        if !(self.variants[index].value & self.field_bits).is_zero() {
+            let span = self.variants[index].value_span();
+            self.errors.add(span, format!("Composite flag `{}` uses bits that belong to a field !", self.variants[index].name.as_str()));
+            return;
+        }
+        if self.variants[index].value.is_zero() {
+            let span = self.variants[index].value_span();
+            self.errors.add(span, format!("Composite flag `{}` has no bits set (its value is 0). Use the 'empty' attribute to name the empty variant instead !", self.variants[index].name.as_str()));
+            return;
+        }
+        self.all_set_bits |= self.variants[index].value;
+    }
+    // the key used to detect duplicate names (names are compared case-insensitive unless `case_sensitive` is set)
+    fn name_key(&self, name: &str) -> String {
+        if self.args.case_sensitive {
+            return String::from(name);
+        }
+        return name.to_lowercase();
+    }
+    // value of a flag referred by name from the value of the flag with index `current`
+    fn resolve_flag(&self, ident: &Ident, current: usize) -> Result<Value, String> {
+        let name = ident.to_string();
+        if let Some(index) = self.map_names.get(&self.name_key(name.as_str())) {
+            if self.variants[*index].name != name {
+                return Err(format!("Unknown flag `{}` (did you mean `{}` ?)", name, self.variants[*index].name.as_str()));
+            }
+            if self.variants[*index].is_field() {
+                return Err(format!("`{}` is a field and can not be used in the value of a flag", name));
+            }
+            if (*index < current) && !self.variants[*index].cfg.is_empty() && (self.variants[*index].cfg != self.variants[current].cfg) {
+                return Err(format!("Flag `{}` has #[cfg] attributes and can only be used in the value of a flag with the same #[cfg] attributes", name));
+            }
+            if *index < current {
+                return Ok(self.variants[*index].value);
+            }
+            if *index == current {
+                return Err(format!("Flag `{}` can not be defined using itself", name));
+            }
+            return Err(format!("Flag `{}` must be defined before flag `{}` in order to be used in its value", name, self.variants[current].name.as_str()));
+        }
+        if self.invalid_names.contains(&name) {
+            return Err(format!("Flag `{}` can not be used because its declaration is invalid", name));
+        }
+        return Err(format!("Unknown flag `{}` (a composite value can only use the flags defined before it in the enum)", name));
+    }
+    // flags without a value get the next free bit (starting from `auto_start`), skipping the bits
+    // already used by the flags with explicit values
+    fn assign_auto_values(&mut self) {
+        let bits = self.args.flags_type.bits();
+        if self.args.auto_start >= bits {
+            self.errors.add(self.args.auto_start_span, format!("The value for `auto_start` attribute must be the index of a bit (a number between 0 and {} for flags stored on {} bits). Provided value was: {}", bits - 1, bits, self.args.auto_start));
+            return;
+        }
+        let mut bit = self.args.auto_start;
+        for index in 0..self.variants.len() {
+            if !self.variants[index].auto {
+                continue;
+            }
+            while (bit < bits) && !(self.all_set_bits & Value::bit(bit)).is_zero() {
+                bit += 1;
+            }
+            if bit >= bits {
+                self.errors.add(self.variants[index].span, format!("There are no free bits left (starting from bit {}) for flag `{}`. Enum is set to store data on {} bits. Change the representation by using the attribute bits or set the value of the flag explicitly !", self.args.auto_start, self.variants[index].name.as_str(), bits));
+                continue;
+            }
+            self.variants[index].value = Value::bit(bit);
+            self.validate_flag_value(index);
+        }
+    }
+    // a field is declared as `Name = field(<first bit>..<end bit>)` or `Name = field(<first bit>..=<last bit>)`
+    // a group is declared as `Name = group(<range>, Member_1 = <value>, Member_2 = <value>, ...)`
+    // returns the content of the parenthesis and true for a group
+    fn field_range(tokens: &[TokenTree]) -> Option<(Group, bool)> {
+        if let [TokenTree::Ident(ident), TokenTree::Group(group)] = tokens {
+            if group.delimiter() == Delimiter::Parenthesis {
+                match ident.to_string().as_str() {
+                    "field" => return Some((group.clone(), false)),
+                    "group" => return Some((group.clone(), true)),
+                    _ => {}
+                }
+            }
+        }
+        return None;
+    }
+    fn validate_field(&mut self, index: usize, range: &Group, is_group: bool) {
+        if let Some(span) = self.variants[index].cfg_span {
+            self.errors.add(span, format!("Fields and groups (`{}`) can not have #[cfg] attributes", self.variants[index].name.as_str()));
+            return;
+        }
+        if self.args.flags_type.is_wide() {
+            self.errors.add(range.span(), format!("Fields and groups (`{}`) are only supported for flags stored on at most 128 bits", self.variants[index].name.as_str()));
+            return;
+        }
+        if self.args.flags_type.is_signed() {
+            self.errors.add(range.span(), format!("Fields and groups (`{}`) are only supported for unsigned types (the flags are stored as {})", self.variants[index].name.as_str(), self.args.flags_type.as_str()));
+            return;
+        }
+        let mut tokens: Vec<TokenTree> = range.stream().into_iter().collect();
+        let mut members = Vec::new();
+        if is_group {
+            if let Some(pos) = tokens.iter().position(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == ',')) {
+                members = tokens.split_off(pos + 1);
+                tokens.pop();
+            }
+        }
+        let is_dot = |token: Option<&TokenTree>| matches!(token, Some(TokenTree::Punct(p)) if p.as_char() == '.');
+        let pos = (0..tokens.len()).find(|i| is_dot(tokens.get(*i)) && is_dot(tokens.get(*i + 1)));
+        let pos = match pos {
+            Some(pos) => pos,
+            None => {
+                self.errors.add(range.span(), format!("Expecting a range of bits for field `{}` (for example: field(4..7) or field(4..=6))", self.variants[index].name.as_str()));
+                return;
+            }
+        };
+        let inclusive = matches!(tokens.get(pos + 2), Some(TokenTree::Punct(p)) if p.as_char() == '=');
+        let end_pos = pos + if inclusive { 3 } else { 2 };
+        let unresolved = |ident: &Ident| Err(format!("Unknown flag `{}` (the range of a field can only use numbers)", ident));
+        let max_value = self.args.flags_type.max_value();
+        let start = Expression::new(tokens[..pos].to_vec(), range.span_open(), max_value, self.args.flags_type, &unresolved).evaluate();
+        let end = Expression::new(tokens[end_pos..].to_vec(), range.span_close(), max_value, self.args.flags_type, &unresolved).evaluate();
+        let (start, end) = match (start, end) {
+            (Ok(start), Ok(end)) => (start, end),
+            (Err((span, message)), _) | (_, Err((span, message))) => {
+                self.errors.add(span, message);
+                return;
+            }
+        };
+        // the bounds are checked while they are still `Value`s (so that they are not truncated when converted to u32)
+        let bits = self.args.flags_type.bits();
+        let limit = Value::from(bits as u128);
+        if start >= limit {
+            let span = tokens.first().map(|t| t.span()).unwrap_or(range.span());
+            self.errors.add(span, format!("The start of the range of field `{}` ({}) is outside the {} bits used to store the flags", self.variants[index].name.as_str(), start, bits));
+            return;
+        }
+        if (end > limit) || (inclusive && (end == limit)) {
+            let span = tokens.get(end_pos).map(|t| t.span()).unwrap_or(range.span());
+            self.errors.add(span, format!("The end of the range of field `{}` ({}) is outside the {} bits used to store the flags", self.variants[index].name.as_str(), end, bits));
+            return;
+        }
+        // both values are now at most the number of bits (at most 4096)
+        let (start, mut end) = (start.as_u128().unwrap() as u32, end.as_u128().unwrap() as u32);
+        if inclusive {
+            end = end.checked_add(1).unwrap();
+        }
+        if start >= end {
+            self.errors.add(range.span(), format!("Invalid range of bits for field `{}`: the range must not be empty", self.variants[index].name.as_str()));
+            return;
+        }
+        let width = end - start;
+        if width > 128 {
+            self.errors.add(range.span(), format!("Field `{}` has {} bits (a field can have at most 128 bits)", self.variants[index].name.as_str(), width));
+            return;
+        }
+        let mask = Value::ones(width) << start;
+        let name = self.variants[index].name.clone();
+        if !(mask & self.all_set_bits).is_zero() {
+            self.errors.add(range.span(), format!("Field `{}` (bits {}..{}) uses bits that belong to other flags or fields !", name.as_str(), start, end));
+            return;
+        }
+        let method = super::utils::to_snake_case(name.as_str());
+        // (a field whose name is the same as the method was already reported as a flag)
+        if GENERATED_METHODS.contains(&method.as_str()) && (method != name) {
+            self.errors.add(self.variants[index].span, format!("The name of field `{}` conflicts with the generated method `{}`", name.as_str(), method.as_str()));
+            return;
+        }
+        if let Some(other) = self.variants.iter().find(|v| v.is_field() && (super::utils::to_snake_case(v.name.as_str()) == method)) {
+            self.errors.add(self.variants[index].span, format!("Fields `{}` and `{}` have the same accessor name (`{}`)", other.name.as_str(), name.as_str(), method.as_str()));
+            return;
+        }
+        self.variants[index].field = Some((start, width));
+        self.variants[index].value = mask;
+        self.field_bits |= mask;
+        self.all_set_bits |= mask;
+        if is_group {
+            self.validate_group_members(index, members, range.span_close());
+        }
+    }
+    // members are declared as `Name = <value>` where the value is relative to the first bit of the group
+    fn validate_group_members(&mut self, index: usize, tokens: Vec<TokenTree>, end_span: Span) {
+        let (start, width) = self.variants[index].field.unwrap();
+        let group_name = self.variants[index].name.clone();
+        if let Err(message) = self.validate_group_name(group_name.as_str()) {
+            self.errors.add(self.variants[index].span, message);
+            return;
+        }
+        let max_member = Value::ones(width);
+        let unresolved = |ident: &Ident| Err(format!("Unknown flag `{}` (the value of a group member can only use numbers)", ident));
+        let mut members: Vec<(String, Value)> = Vec::new();
+        for member in tokens.split(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == ',')) {
+            if member.is_empty() {
+                continue;
+            }
+            let name = match member {
+                [TokenTree::Ident(name), TokenTree::Punct(equal), ..] if equal.as_char() == '=' => name,
+                _ => {
+                    self.errors.add(member[0].span(), format!("Expecting a member of group `{}` with the format `Name = value`", group_name.as_str()));
+                    return;
+                }
+            };
+            let member_name = name.to_string();
+            let value = match Expression::new(member[2..].to_vec(), end_span, max_member, self.args.flags_type, &unresolved).evaluate() {
+                Ok(value) => value,
+                Err((span, message)) => {
+                    self.errors.add(span, message);
+                    return;
+                }
+            };
+            if value > max_member {
+                self.errors.add(member[2].span(), format!("The value of member `{}` ({}) does not fit in the {} bits of group `{}`", member_name.as_str(), value, width, group_name.as_str()));
+                return;
+            }
+            if let Some((other, _)) = members.iter().find(|(other, other_value)| (*other == member_name) || (*other_value == value)) {
+                self.errors.add(name.span(), format!("Member `{}` of group `{}` has the same name or value as member `{}`", member_name.as_str(), group_name.as_str(), other.as_str()));
+                return;
+            }
+            members.push((member_name, value));
+        }
+        if members.is_empty() {
+            self.errors.add(end_span, format!("Group `{}` must have at least one member (for example: group({}..{}, A = 1, B = 2))", group_name.as_str(), start, start + width));
+            return;
+        }
+        self.variants[index].members = members;
+    }
+    // the enum generated for a group is named <Name><Group> so it must not be the same as other generated types
+    fn validate_group_name(&self, name: &str) -> Result<(), String> {
+        for suffix in GENERATED_TYPE_SUFFIXES {
+            if name == *suffix {
+                return Err(format!("Group `{}` can not be used as its enum name ({}{}) is already used by a generated type", name, self.name.as_str(), name));
+            }
+        }
+        return Ok(());
+    }
+    // the smallest unsigned type that can hold the value of a field
+    fn field_type(width: u32) -> &'static str {
+        match width {
+            0..=8 => return "u8",
+            9..=16 => return "u16",
+            17..=32 => return "u32",
+            33..=64 => return "u64",
+            _ => return "u128",
+        }
+    }
+    fn add_fields(&mut self) {
+        let mut table = String::new();
+        let mut count = 0;
+        let mut field_bits = Value::ZERO;
+        for variant in self.variants.iter() {
+            let (start, width) = match variant.field {
+                Some(field) => field,
+                None => continue,
+            };
+            field_bits |= variant.value;
+            // the attributes of the variant (docs, `deprecated`, ...) are added to every generated accessor
+            let method = super::utils::to_snake_case(variant.name.as_str());
+            let field_type = Parser::field_type(width);
+            let mask = self.literal(variant.value);
+            let check = if width < field_type[1..].parse::<u32>().unwrap() {
+                format!("assert!(value <= 0x{:X}, \"The value for field {} does not fit in {} bits\");", Value::ones(width), variant.name, width)
+            } else {
+                String::new()
+            };
+            if variant.is_group() {
+                let mut getter = String::new();
+                let mut setter = String::new();
+                let mut members = String::new();
+                for (member, value) in variant.members.iter() {
+                    let value = self.literal(*value);
+                    getter.push_str(&format!("{} => Some($$(NAME)$${}::{}), ", value, variant.name, member));
+                    setter.push_str(&format!("$$(NAME)$${}::{} => {}, ", variant.name, member, value));
+                    members.push_str(&format!("(\"{}\", {}), ", member, value));
+                }
+                self.output.push_str(&format!(
+                    r#"
+{attributes}        /// Returns the active member of the `{name}` group (bits {start}..{end}) or None if the bits of the group do not match any member.
+        #[inline(always)]
+        $$(VISIBILITY)$$ const fn {method}(&self) -> Option<$$(NAME)$${name}> {{
+            match (self.value & {mask}) >> {start} {{ {getter}_ => None }}
+        }}
+{attributes}        /// Sets the active member of the `{name}` group (the bits of the group are cleared first).
+        #[inline(always)]
+        $$(VISIBILITY)$$ fn set_{method}(&mut self, member: $$(NAME)$${name}) {{
+            let value: $$(BITS)$$ = match member {{ {setter} }};
+            self.value = (self.value & !{mask}) | (value << {start});
+        }}
+{attributes}        /// Clears all the bits of the `{name}` group.
+        #[inline(always)]
+        $$(VISIBILITY)$$ fn clear_{method}(&mut self) {{
+            self.value &= !{mask};
+        }}
+"#,
+                    name = variant.name,
+                    end = start + width,
+                    attributes = variant.attributes,
+                ));
+                table.push_str(&format!("(\"{}\", {}, {}, &[{}]), ", variant.name, start, mask, members));
+                count += 1;
+                continue;
+            }
+            self.output.push_str(&format!(
+                r#"
+{attributes}        /// Returns the value of the `{name}` field (bits {start}..{end}).
+        #[inline(always)]
+        $$(VISIBILITY)$$ const fn {method}(&self) -> {field_type} {{
+            ((self.value & {mask}) >> {start}) as {field_type}
+        }}
+{attributes}        /// Sets the value of the `{name}` field (bits {start}..{end}) without changing the other bits.
+        /// 
+        /// # Panics
+        /// 
+        /// Panics if `value` does not fit in the {width} bits of the field.
+        #[inline(always)]
+        $$(VISIBILITY)$$ fn set_{method}(&mut self, value: {field_type}) {{
+            {check}
+            self.value = (self.value & !{mask}) | ((value as $$(BITS)$$) << {start});
+        }}
+"#,
+                name = variant.name,
+                end = start + width,
+                attributes = variant.attributes,
+            ));
+            table.push_str(&format!("(\"{}\", {}, {}, &[]), ", variant.name, start, mask));
+            count += 1;
+        }
+        // (name, first bit, mask, group members) for every field and group
+        self.output.push_str(&format!("\tconst __FIELDS: [(&'static str, u32, $$(BITS)$$, &'static [(&'static str, $$(BITS)$$)]); {}] = [{}];\n", count, table));
+        self.output.push_str(&format!("\tconst __FIELD_BITS: $$(BITS)$$ = {};\n", self.literal(field_bits)));
+        self.output.push_str(
+            r#"
+        // `Name=value` (or `Name=Member` for groups) for every field and group that is not 0
+        // (the groups that have a member with the value 0 are always shown)
+        fn __field_texts(&self) -> impl Iterator<Item = String> + '_ {
+            $$(NAME)$$::__FIELDS.into_iter().filter_map(move |(name, start, mask, members)| {
+                let value = (self.value & mask) >> start;
+                let member = members.iter().find(|(_, member)| *member == value);
+                if (value == $$(ZERO)$$) && member.is_none() {
+                    return None;
+                }
+                match member {
+                    Some((member, _)) => Some(format!("{}={}", name, member)),
+                    None => Some(format!("{}={}", name, value)),
+                }
+            })
+        }
+"#,
+        );
+    }
+    // an enum (<Name><Group>) with the members of every group
+    pub fn add_groups(&mut self) {
+        for variant in self.variants.iter() {
+            if !variant.is_group() {
+                continue;
+            }
+            let members: Vec<&str> = variant.members.iter().map(|(name, _)| name.as_str()).collect();
+            self.output.push_str(&format!(
+                "\n/// The members of the `{name}` group of `$$(NAME)$$`.\n#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]\n$$(VISIBILITY)$$ enum $$(NAME)$${name} {{ {members} }}\n",
+                name = variant.name,
+                members = members.join(", "),
+            ));
+        }
+    }
+    // bits=auto -> the smallest unsigned type that can store all the flags
+    fn select_auto_bits(&mut self) {
+        let used = self.all_set_bits.bits();
+        self.args.flags_type = match used {
+            0..=8 => FlagsType::U8,
+            9..=16 => FlagsType::U16,
+            17..=32 => FlagsType::U32,
+            33..=64 => FlagsType::U64,
+            _ => FlagsType::U128,
+        };
+    }
+    // computes the values of all variants: first the ones defined through literals, then the ones
+    // without a value and at the end the composite ones (defined using other flags)
+    fn compute_values(&mut self) {
+        let unresolved = |ident: &Ident| Err(format!("Unknown flag `{}`", ident));
+        for index in 0..self.variants.len() {
+            if self.variants[index].auto {
+                continue;
+            }
+            if let Some((range, is_group)) = Parser::field_range(&self.variants[index].tokens) {
+                self.validate_field(index, &range, is_group);
+                continue;
+            }
+            if Expression::has_names(&self.variants[index].tokens) {
+                self.variants[index].composite = true;
+                continue;
+            }
+            let tokens = self.variants[index].tokens.clone();
+            let max_value = self.args.flags_type.max_value();
+            match Expression::new(tokens, self.variants[index].value_span, max_value, self.args.flags_type, &unresolved).evaluate() {
+                Ok(value) => {
+                    self.variants[index].value = value;
+                    self.validate_flag_value(index);
+                }
+                Err((span, message)) => self.errors.add(span, message),
+            }
+        }
+        self.assign_auto_values();
+        // the bits of the flags with #[cfg] attributes are not part of the mask used by `!` (they may not exist)
+        let mut mask = self.all_set_bits & !self.field_bits;
+        for variant in self.variants.iter().filter(|v| !v.cfg.is_empty()) {
+            mask &= !variant.value;
+        }
+        for index in 0..self.variants.len() {
+            if !self.variants[index].composite {
+                continue;
+            }
+            let tokens = self.variants[index].tokens.clone();
+            let resolve = |ident: &Ident| self.resolve_flag(ident, index);
+            let result = Expression::new(tokens, self.variants[index].value_span, mask, self.args.flags_type, &resolve).evaluate();
+            match result {
+                Ok(value) => {
+                    self.variants[index].value = value;
+                    self.validate_composite_value(index);
+                }
+                Err((span, message)) => self.errors.add(span, message),
+            }
+        }
+    }
+    // resolves the flags from the `implies` attributes and checks that there are no cycles
+    fn compute_implications(&mut self) {
+        for index in 0..self.variants.len() {
+            let implies = std::mem::take(&mut self.variants[index].implies);
+            if implies.is_empty() {
+                continue;
+            }
+            if self.variants[index].is_field() {
+                self.errors.add(implies[0].span(), format!("`{}` is a field and can not imply other flags", self.variants[index].name.as_str()));
+                continue;
+            }
+            if self.variants[index].value.is_zero() {
+                self.errors.add(implies[0].span(), format!("Flag `{}` has no bits set and can not imply other flags", self.variants[index].name.as_str()));
+                continue;
+            }
+            if !self.variants[index].cfg.is_empty() {
+                self.errors.add(implies[0].span(), format!("Flag `{}` has #[cfg] attributes and can not imply other flags", self.variants[index].name.as_str()));
+                continue;
+            }
+            for ident in implies.iter() {
+                let name = ident.to_string();
+                match self.map_names.get(&self.name_key(name.as_str())) {
+                    Some(implied) if self.variants[*implied].name == name => {
+                        if self.variants[*implied].is_field() {
+                            self.errors.add(ident.span(), format!("`{}` is a field and can not be implied by a flag", name.as_str()));
+                        } else if !self.variants[*implied].cfg.is_empty() {
+                            self.errors.add(ident.span(), format!("Flag `{}` has #[cfg] attributes and can not be implied by a flag", name.as_str()));
+                        } else if *implied == index {
+                            self.errors.add(ident.span(), format!("Flag `{}` can not imply itself", name.as_str()));
+                        } else if !self.variants[index].implied.contains(implied) {
+                            self.variants[index].implied.push(*implied);
+                        }
+                    }
+                    _ => self.errors.add(ident.span(), format!("Unknown flag `{}` in the `implies` attribute of flag `{}`", name.as_str(), self.variants[index].name.as_str())),
+                }
+            }
+            self.variants[index].implies = implies;
+        }
+        if !self.errors.is_empty() {
+            return;
+        }
+        // depth first search: 0 = not visited, 1 = on the current path, 2 = done
+        let mut marks = vec![0u8; self.variants.len()];
+        let mut path = Vec::new();
+        for index in 0..self.variants.len() {
+            if let Some(cycle) = self.find_implication_cycle(index, &mut marks, &mut path) {
+                let names: Vec<&str> = cycle.iter().map(|i| self.variants[*i].name.as_str()).collect();
+                self.errors.add(self.variants[cycle[0]].implies[0].span(), format!("Cyclic implication between flags: {}", names.join(" -> ")));
+                return;
+            }
+        }
+    }
+    // resolves the flags used by the `exclusive` and `requires` attributes
+    fn compute_constraints(&mut self) {
+        let mut constraints = std::mem::take(&mut self.constraints);
+        for constraint in constraints.iter_mut() {
+            for ident in constraint.names.iter() {
+                let name = ident.to_string();
+                let index = match self.map_names.get(&self.name_key(name.as_str())) {
+                    Some(index) if self.variants[*index].name == name => *index,
+                    _ => {
+                        self.errors.add(ident.span(), format!("Unknown flag `{}` in the `{}` attribute", name.as_str(), constraint.attribute_name()));
+                        continue;
+                    }
+                };
+                if self.variants[index].is_field() {
+                    self.errors.add(ident.span(), format!("`{}` is a field and can not be used in the `{}` attribute", name.as_str(), constraint.attribute_name()));
+                } else if self.variants[index].value.is_zero() {
+                    self.errors.add(ident.span(), format!("Flag `{}` has no bits set and can not be used in the `{}` attribute", name.as_str(), constraint.attribute_name()));
+                } else if !self.variants[index].cfg.is_empty() {
+                    self.errors.add(ident.span(), format!("Flag `{}` has #[cfg] attributes and can not be used in the `{}` attribute", name.as_str(), constraint.attribute_name()));
+                } else if constraint.flags.contains(&index) {
+                    self.errors.add(ident.span(), format!("Flag `{}` is used twice in the `{}` attribute", name.as_str(), constraint.attribute_name()));
+                } else {
+                    constraint.flags.push(index);
+                }
+            }
+            if constraint.kind == ConstraintKind::Exclusive {
+                // two exclusive flags that share bits can never be set at the same time
+                for (position, first) in constraint.flags.iter().enumerate() {
+                    for second in constraint.flags[position + 1..].iter() {
+                        if !(self.variants[*first].value & self.variants[*second].value).is_zero() {
+                            self.errors.add(constraint.span, format!("Flags `{}` and `{}` share bits and can not be exclusive", self.variants[*first].name.as_str(), self.variants[*second].name.as_str()));
+                        }
+                    }
+                }
+            }
+        }
+        self.constraints = constraints;
+    }
+    fn find_implication_cycle(&self, index: usize, marks: &mut Vec<u8>, path: &mut Vec<usize>) -> Option<Vec<usize>> {
+        if marks[index] == 2 {
+            return None;
+        }
+        if marks[index] == 1 {
+            let start = path.iter().position(|i| *i == index).unwrap();
+            let mut cycle = path[start..].to_vec();
+            cycle.push(index);
+            return Some(cycle);
+        }
+        marks[index] = 1;
+        path.push(index);
+        for implied in self.variants[index].implied.iter() {
+            if let Some(cycle) = self.find_implication_cycle(*implied, marks, path) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        marks[index] = 2;
+        return None;
+    }
+    fn add_implications(&mut self) {
+        let mut count = 0;
+        let mut table = String::new();
+        for variant in self.variants.iter() {
+            if variant.implied.is_empty() {
+                continue;
+            }
+            let implied = variant.implied.iter().fold(Value::ZERO, |mask, index| mask | self.variants[*index].value);
+            table.push_str(&format!("({}, {}), ", self.literal(variant.value), self.literal(implied)));
+            count += 1;
+        }
+        // (flag, flags implied by it) for every `implies` attribute
+        self.output.push_str(&format!("\tconst __IMPLIES: [($$(BITS)$$, $$(BITS)$$); {}] = [{}];\n", count, table));
+    }
+    fn literal(&self, value: Value) -> String {
+        if let FlagsType::Words(count) = self.args.flags_type {
+            let words: Vec<String> = value.words(count as usize).iter().map(|word| format!("0x{:X}u64", word)).collect();
+            return format!("$$(NAME)$$Value([{}])", words.join(", "));
+        }
+        let flags_type = self.args.flags_type;
+        if flags_type.is_signed() && (value.bits() == flags_type.bits()) {
+            // the sign bit is set -> the literal would be out of range for the signed type
+            return format!("(0x{:X}{} as {})", value, flags_type.unsigned().as_str(), flags_type.as_str());
+        }
+        return format!("0x{:X}{}", value, flags_type.as_str());
+    }
+    // the value of a variant (0 when the `#[cfg]` attributes of the variant are not satisfied)
+    fn conditional_literal(&self, variant: &Variant) -> String {
+        match variant.cfg_condition() {
+            Some(condition) => return format!("(if cfg!({}) {{ {} }} else {{ {} }})", condition, self.literal(variant.value), self.literal(Value::ZERO)),
+            None => return self.literal(variant.value),
+        }
+    }
+    // the bits of `mask` without the ones that only belong to flags with `#[cfg]` attributes, followed by the
+    // values of these flags (each one is only added if its `#[cfg]` attributes are satisfied)
+    fn conditional_mask(&self, mask: Value) -> String {
+        let mut cfg_bits = Value::ZERO;
+        let mut plain_bits = Value::ZERO;
+        for variant in self.variants.iter() {
+            if variant.cfg.is_empty() {
+                plain_bits |= variant.value;
+            } else {
+                cfg_bits |= variant.value;
             }
-            self.state = State::ExpectFlag;
-        } else {
-            panic!("Expecting ',' separator but got:  {:?}", token);
         }
+        let base = (mask & !cfg_bits) | (mask & plain_bits);
+        let mut expression = self.literal(base);
+        for variant in self.variants.iter() {
+            if variant.cfg.is_empty() || (variant.value & !base).is_zero() {
+                continue;
+            }
+            if self.args.flags_type.is_wide() {
+                expression = format!("{}.__or({})", expression, self.conditional_literal(variant));
+            } else {
+                expression = format!("{} | {}", expression, self.conditional_literal(variant));
+            }
+        }
+        return expression;
+    }
+    // the number of variants that are not fields (the ones with `#[cfg]` attributes are only counted if they exist)
+    fn variants_count(&self) -> String {
+        let mut count = self.variants.iter().filter(|v| !v.is_field() && v.cfg.is_empty()).count().to_string();
+        for variant in self.variants.iter() {
+            if let Some(condition) = variant.cfg_condition() {
+                count.push_str(format!(" + (cfg!({}) as usize)", condition).as_str());
+            }
+        }
+        return count;
     }
     pub fn parse(&mut self, input: TokenStream) {
         for token in input.into_iter() {
             match self.state {
                 State::ExpectVisibility => self.validate_expect_visibility(token),
+                State::ExpectAttribute => self.validate_expect_attribute(token),
                 State::ExpectVisibilityGroup => self.validate_expect_visibility_group(token),
                 State::ExpectEnum => self.validate_expect_enum(token),
                 State::ExpectName => self.validate_expect_enum_name(token),
                 State::ExpectOpenBrace => self.validate_expect_open_brace(token),
                 State::ExpectFlag => self.validate_expect_flag(token),
+                State::ExpectFlagAttribute => self.validate_expect_flag_attribute(token),
                 State::ExpectEqual => self.validate_expect_equal(token),
                 State::ExpectValue => self.validate_expect_value(token),
-                State::ExpectComma => self.validate_expect_comma(token),
+                State::SkipUntilComma => self.skip_until_comma(token),
+                State::Stop => {}
+            }
+        }
+    }
+    // c_header=true -> writes `<Name>.h` (a typedef and a #define for every flag) in OUT_DIR
+    pub fn write_c_header(&mut self) {
+        if !self.args.c_header || self.has_errors() {
+            return;
+        }
+        let span = self.args.c_header_span;
+        let mut defines = Vec::new();
+        if !self.args.disable_empty_generation {
+            defines.push(Define::Value(c_header::upper(self.args.none_case.as_str()), Value::ZERO));
+        }
+        for variant in self.variants.iter() {
+            let name = c_header::upper(variant.name.as_str());
+            match variant.field {
+                Some((start, _)) => {
+                    defines.push(Define::Field(name.clone(), variant.value, start));
+                    for (member, value) in variant.members.iter() {
+                        defines.push(Define::Value(format!("{}_{}", name, c_header::upper(member)), *value << start));
+                    }
+                }
+                None => defines.push(Define::Value(name, variant.value)),
+            }
+        }
+        let header = match c_header::build(self.name.as_str(), self.args.flags_type, &defines) {
+            Some(header) => header,
+            None => {
+                self.errors.add(span, format!("A C header can not be generated for flags stored as {} (only types of at most 64 bits are supported)", self.args.flags_type.as_str()));
+                return;
             }
+        };
+        let out_dir = match std::env::var("OUT_DIR") {
+            Ok(out_dir) => out_dir,
+            Err(_) => {
+                self.errors.add(span, String::from("The OUT_DIR environment variable is not set (a build script is required for the crate to use `c_header`)"));
+                return;
+            }
+        };
+        // the file is only written when the content changes (to avoid rebuilding the C side)
+        let path = std::path::Path::new(out_dir.as_str()).join(format!("{}.h", self.name));
+        if std::fs::read_to_string(&path).ok().as_deref() == Some(header.as_str()) {
+            return;
         }
+        if let Err(error) = std::fs::write(&path, header) {
+            self.errors.add(span, format!("Failed to write the C header {}: {}", path.display(), error));
+        }
+    }
+    pub fn has_errors(&self) -> bool {
+        return !(self.args.errors.is_empty() && self.errors.is_empty());
+    }
+    pub fn errors_stream(&self) -> TokenStream {
+        let mut stream = TokenStream::new();
+        self.args.errors.extend_stream(&mut stream);
+        self.errors.extend_stream(&mut stream);
+        return stream;
     }
     pub fn add_methods(&mut self) {
+        // add the flags (as associated constants)
+        for variant in self.variants.iter() {
+            if variant.is_field() {
+                continue;
+            }
+            self.output.push_str(variant.attributes.as_str());
+            self.output.push_str("\t$$(VISIBILITY)$$ const ");
+            self.output.push_str(variant.name.as_str());
+            self.output.push_str(": $$(NAME)$$ = $$(NAME)$$ { value: ");
+            self.output.push_str(self.literal(variant.value).as_str());
+            self.output.push_str(" };\n");
+        }
+        self.add_flags_table();
+        self.add_fields();
+        self.add_implications();
+        self.output.push_str("\t/// The bits of all the flags declared in the enum (including the bits of the fields and groups).\n");
+        self.output.push_str("\t$$(VISIBILITY)$$ const ALL_BITS: $$(BITS)$$ = $$(ALL_SET_BITS)$$;\n");
+        // the bits of the flags, without the fields (used by `complement`, `all` and `is_all`)
+        self.output.push_str(&format!("\tconst __FLAG_BITS: $$(BITS)$$ = {};\n", self.conditional_mask(self.all_set_bits & !self.field_bits)));
         // add empty case if needed
         if (!self.has_empty_value) && (self.args.disable_empty_generation == false) {
             self.output.push_str(
                 r#"
-            $$(VISIBILITY)$$ const $$(EMPTY)$$: $$(NAME)$$ = $$(NAME)$$ { value: 0 };
+            $$(VISIBILITY)$$ const $$(EMPTY)$$: $$(NAME)$$ = $$(NAME)$$ { value: $$(ZERO)$$ };
             "#,
             );
         }
@@ -258,13 +1190,59 @@ impl Parser {
         ///     eprintln!("Could not create value!");
         /// }
         /// ```        
-        $$(VISIBILITY)$$ fn from_value(value: $$(BITS)$$) -> Option<Self> {
+        $$(VISIBILITY)$$ $$(CONST)$$fn from_value(value: $$(BITS)$$) -> Option<Self> {
             $$(DISABLE_EMPTY_CODE)$$
-            if value & $$(ALL_SET_BITS)$$ as $$(BITS)$$ == value {
+            if value & $$(NAME)$$::ALL_BITS == value {
                 return Some($$(NAME)$$ { value } );
             }
             None
         } 
+        /// Creates a new $$(NAME)$$ object from an $$(BITS)$$ value, dropping the bits that do not
+        /// belong to any flag declared in the enum.$$(EMPTY_VALUE_DOC)$$
+        /// 
+        /// # Example
+        /// ```rust
+        /// use EnumBitFlags::EnumBitFlags;
+        /// 
+        /// #[EnumBitFlags]
+        /// enum MyFlags { A = 1, B = 2 }
+        /// 
+        /// assert_eq!(MyFlags::from_value_truncate(0x13), MyFlags::A | MyFlags::B);
+        /// ```
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn from_value_truncate(value: $$(BITS)$$) -> Self {
+            $$(NAME)$$ { value: value & $$(NAME)$$::ALL_BITS }
+        }
+        /// Creates a new $$(NAME)$$ object from an $$(BITS)$$ value, keeping all the bits (including the ones
+        /// that do not belong to any flag declared in the enum). The unknown bits can be inspected via the
+        /// `unknown_bits` and `has_unknown_bits` methods and are kept by `get_value`.
+        /// 
+        /// # Example
+        /// ```rust
+        /// use EnumBitFlags::EnumBitFlags;
+        /// 
+        /// #[EnumBitFlags]
+        /// enum MyFlags { A = 1, B = 2 }
+        /// 
+        /// let f = MyFlags::from_value_retain(0x11);
+        /// assert!(f.contains(MyFlags::A));
+        /// assert_eq!(f.unknown_bits(), 0x10);
+        /// assert_eq!(f.get_value(), 0x11);
+        /// ```
+        #[inline(always)]
+        $$(VISIBILITY)$$ const fn from_value_retain(value: $$(BITS)$$) -> Self {
+            $$(NAME)$$ { value }
+        }
+        /// Creates a new $$(NAME)$$ object from an $$(BITS)$$ value without any validation.
+        /// 
+        /// # Safety
+        /// 
+        /// The caller must guarantee that `value` is a valid bit configuration (the same configurations
+        /// that are accepted by `from_value`). Use `from_value_retain` to explicitly keep unknown bits.
+        #[inline(always)]
+        $$(VISIBILITY)$$ const unsafe fn from_value_unchecked(value: $$(BITS)$$) -> Self {
+            $$(NAME)$$ { value }
+        }
        
         /// Checks if all the values in the specified `mask` are set
         /// within the internal value of the current object.
@@ -278,8 +1256,8 @@ impl Parser {
         /// - `true` if all values in the `mask` are set in the current value.
         /// - `false` otherwise.
         #[inline(always)]
-        $$(VISIBILITY)$$ fn contains(&self, mask: $$(NAME)$$) -> bool { 
-            return ((self.value & mask.value) == mask.value) && (mask.value!=0);
+        $$(VISIBILITY)$$ $$(CONST)$$fn contains(&self, mask: $$(NAME)$$) -> bool { 
+            return ((self.value & mask.value) == mask.value) && (mask.value != $$(ZERO)$$);
         }
         /// Checks if at least on of the values in the specified `mask` are set
         /// within the internal value of the current object.
@@ -293,8 +1271,25 @@ impl Parser {
         /// - `true` if at least one value in the `mask` is set in the current value.
         /// - `false` otherwise.
         #[inline(always)]
-        $$(VISIBILITY)$$ fn contains_one(&self, mask: $$(NAME)$$) -> bool { 
-            return (self.value & mask.value) != 0 ;
+        $$(VISIBILITY)$$ $$(CONST)$$fn contains_one(&self, mask: $$(NAME)$$) -> bool { 
+            return (self.value & mask.value) != $$(ZERO)$$;
+        }
+        /// Checks if all the flags set in the current object are also set in `other`
+        /// (the empty value is a subset of any value).
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn is_subset_of(&self, other: $$(NAME)$$) -> bool { 
+            return (self.value & other.value) == self.value;
+        }
+        /// Checks if all the flags set in `other` are also set in the current object
+        /// (any value is a superset of the empty value).
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn is_superset_of(&self, other: $$(NAME)$$) -> bool { 
+            return (self.value & other.value) == other.value;
+        }
+        /// Checks if the current object and `other` have no flags in common.
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn is_disjoint(&self, other: $$(NAME)$$) -> bool { 
+            return (self.value & other.value) == $$(ZERO)$$;
         }
         /// Checks if the current value is not set or if `disable_empty_generation` is `false` and the object is the empty value
         /// # Returns
@@ -302,8 +1297,8 @@ impl Parser {
         /// - `true` if the current value is not set or is the empty value.
         /// - `false` otherwise.
         #[inline(always)]        
-        $$(VISIBILITY)$$ fn is_empty(&self) -> bool { 
-            return self.value == 0;
+        $$(VISIBILITY)$$ $$(CONST)$$fn is_empty(&self) -> bool { 
+            return self.value == $$(ZERO)$$;
         }
         /// Clears the value or sets it to the empty value.
         /// # Returns
@@ -312,7 +1307,7 @@ impl Parser {
         /// - `false` otherwise.
         #[inline(always)]
         $$(VISIBILITY)$$ fn clear(&mut self) {
-            self.value = 0;
+            self.value = $$(ZERO)$$;
         }
         /// Removes the values set in the `mask` parameter from the current value.        
         /// 
@@ -322,7 +1317,7 @@ impl Parser {
         ///         
         #[inline(always)]
         $$(VISIBILITY)$$ fn remove(&mut self, mask: $$(NAME)$$) {
-            self.value = self.value - (self.value & mask.value);
+            self.value &= !mask.value;
         }
         /// Adds the values set in the `mask` parameter to the current value.        
         /// 
@@ -333,6 +1328,60 @@ impl Parser {
         #[inline(always)]
         $$(VISIBILITY)$$ fn set(&mut self, mask: $$(NAME)$$) {
             self.value |= mask.value;
+            $$(AUTO_IMPLY)$$
+        }
+        /// Returns the union of the current value and `other` (the flags that are set in either of them).
+        /// This is equivalent to the `|` operator.
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn union(self, other: $$(NAME)$$) -> $$(NAME)$$ {
+            $$(NAME)$$ { value: self.value | other.value }$$(AUTO_IMPLY_EXPR)$$
+        }
+        /// Returns the intersection of the current value and `other` (the flags that are set in both of them).
+        /// This is equivalent to the `&` operator.
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn intersection(self, other: $$(NAME)$$) -> $$(NAME)$$ {
+            $$(NAME)$$ { value: self.value & other.value }
+        }
+        /// Returns the flags that are set in the current value but not in `other`.
+        /// This is equivalent to the `-` operator.
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn difference(self, other: $$(NAME)$$) -> $$(NAME)$$ {
+            $$(NAME)$$ { value: self.value & !other.value }
+        }
+        /// Returns the flags that are set in exactly one of the current value and `other`.
+        /// This is equivalent to the `^` operator.
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn symmetric_difference(self, other: $$(NAME)$$) -> $$(NAME)$$ {
+            $$(NAME)$$ { value: self.value ^ other.value }
+        }
+        /// Returns the flags that are not set in the current value. Only the bits of the flags declared in
+        /// the enum are considered (unknown bits and the bits of fields and groups are never set in the result).$$(EMPTY_VALUE_DOC)$$
+        /// This is equivalent to the `!` operator.
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn complement(self) -> $$(NAME)$$ {
+            $$(NAME)$$ { value: !self.value & $$(NAME)$$::__FLAG_BITS }
+        }
+        /// The same as the `|` operator (and `union`), usable in `const` contexts
+        /// (for example: `const RW: $$(NAME)$$ = $$(NAME)$$::Read.or($$(NAME)$$::Write);`).
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn or(self, other: $$(NAME)$$) -> $$(NAME)$$ {
+            self.union(other)
+        }
+        /// The same as the `&` operator (and `intersection`), usable in `const` contexts.
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn and(self, other: $$(NAME)$$) -> $$(NAME)$$ {
+            self.intersection(other)
+        }
+        /// The same as the `^` operator (and `symmetric_difference`), usable in `const` contexts.
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn xor(self, other: $$(NAME)$$) -> $$(NAME)$$ {
+            self.symmetric_difference(other)
+        }
+        /// The same as the `!` operator (and `complement`), usable in `const` contexts.
+        #[inline(always)]
+        #[allow(clippy::should_implement_trait)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn not(self) -> $$(NAME)$$ {
+            self.complement()
         }
         /// Returns the underlying `$$(BITS)$$` value for this object.
         /// 
@@ -344,6 +1393,102 @@ impl Parser {
         $$(VISIBILITY)$$ const fn get_value(&self)->$$(BITS)$$ {
             self.value
         }
+        /// Returns an object that has all the flags declared in the enum set (fields and groups are 0).
+        #[inline(always)]
+        $$(VISIBILITY)$$ const fn all() -> $$(NAME)$$ {
+            $$(NAME)$$ { value: $$(NAME)$$::__FLAG_BITS }
+        }
+        /// Checks if all the flags declared in the enum are set in the current value (fields and groups are ignored).
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn is_all(&self) -> bool {
+            (self.value & $$(NAME)$$::__FLAG_BITS) == $$(NAME)$$::__FLAG_BITS
+        }
+        /// Returns the number of bits that are set in the current value.
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn count(&self) -> u32 {
+            self.value.count_ones()
+        }
+        /// Returns the part of the current value that is made of bits of the flags declared in the enum.
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn known_bits(&self) -> $$(NAME)$$ {
+            $$(NAME)$$ { value: self.value & $$(NAME)$$::ALL_BITS }
+        }
+        /// Returns the bits set in the current value that do not belong to any flag declared in the enum.
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn unknown_bits(&self) -> $$(BITS)$$ {
+            self.value & !$$(NAME)$$::ALL_BITS
+        }
+        /// Checks if the current value has bits that do not belong to any flag declared in the enum
+        /// (this is only possible for objects created via `from_value_retain` or `from_value_unchecked`).
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn has_unknown_bits(&self) -> bool {
+            (self.value & !$$(NAME)$$::ALL_BITS) != $$(ZERO)$$
+        }
+        /// Returns the number of variants declared in the enum (including the composite ones, but not the fields).
+        #[inline(always)]
+        $$(VISIBILITY)$$ const fn len() -> usize {
+            $$(VARIANTS_COUNT)$$
+        }
+        /// Returns a copy of the current value that also contains all the flags implied (directly or
+        /// indirectly) by the flags that are set (see the `#[implies(...)]` attribute).
+        $$(VISIBILITY)$$ $$(CONST)$$fn expand(&self) -> $$(NAME)$$ {
+            let mut value = self.value;
+            loop {
+                let mut result = value;
+                let mut index = 0;
+                while index < $$(NAME)$$::__IMPLIES.len() {
+                    let (flag, implied) = $$(NAME)$$::__IMPLIES[index];
+                    if (value & flag) == flag {
+                        result |= implied;
+                    }
+                    index += 1;
+                }
+                if result == value {
+                    return $$(NAME)$$ { value };
+                }
+                value = result;
+            }
+        }
+        /// Returns the smallest set of flags that expands to the same value as the current one
+        /// (the flags implied by other flags that are set are removed).
+        $$(VISIBILITY)$$ $$(CONST)$$fn normalize(&self) -> $$(NAME)$$ {
+            let expanded = self.expand().value;
+            let mut value = expanded;
+            let mut index = 0;
+            while index < $$(NAME)$$::__IMPLIES.len() {
+                let (flag, implied) = $$(NAME)$$::__IMPLIES[index];
+                if (expanded & flag) == flag {
+                    value &= !(implied & !flag);
+                }
+                index += 1;
+            }
+            $$(NAME)$$ { value }
+        }
+        /// Returns an iterator over the named flags that are set in the current value.
+        /// Composite flags (flags with more than one bit) are returned instead of the flags they contain
+        /// and the flags are returned in the alphabetical order of their names (the same as in `Display`).
+        /// 
+        /// # Example
+        /// ```rust
+        /// use EnumBitFlags::EnumBitFlags;
+        /// 
+        /// #[EnumBitFlags]
+        /// enum MyFlags { A = 1, B = 2, C = 4 }
+        /// 
+        /// let flags = MyFlags::A | MyFlags::C;
+        /// let v: Vec<MyFlags> = flags.iter().collect();
+        /// assert_eq!(v, vec![MyFlags::A, MyFlags::C]);
+        /// ```
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn iter(&self) -> $$(NAME)$$Iter {
+            $$(NAME)$$Iter { inner: self.iter_names() }
+        }
+        /// Returns an iterator over the names and values (`(&'static str, $$(NAME)$$)` pairs) of the
+        /// named flags that are set in the current value.
+        #[inline(always)]
+        $$(VISIBILITY)$$ $$(CONST)$$fn iter_names(&self) -> $$(NAME)$$IterNames {
+            $$(NAME)$$IterNames::new(self.value)
+        }
     }
 
         "#,
@@ -355,7 +1500,7 @@ impl Parser {
         impl std::ops::BitOr for $$(NAME)$$ {
             type Output = Self;        
             #[inline(always)]
-            fn bitor(self, rhs: Self) -> Self::Output { $$(NAME)$$ {value: self.value | rhs.value } }            
+            fn bitor(self, rhs: Self) -> Self::Output { $$(NAME)$$ {value: self.value | rhs.value }$$(AUTO_IMPLY_EXPR)$$ }            
         }"#);
 
         // suport for bitorassign '|=' operations
@@ -363,7 +1508,7 @@ impl Parser {
             r#"
         impl std::ops::BitOrAssign for $$(NAME)$$ {   
             #[inline(always)]
-            fn bitor_assign(&mut self, rhs: Self)  { self.value |= rhs.value; }            
+            fn bitor_assign(&mut self, rhs: Self)  { self.value |= rhs.value; $$(AUTO_IMPLY)$$ }            
         }"#,
         );
 
@@ -384,87 +1529,922 @@ impl Parser {
         }"#,
         );
 
-        // suport for partial EQ '==' and '!=' operations
+        // suport for bitxor '^' operations
+        self.output.push_str(r#"
+        impl std::ops::BitXor for $$(NAME)$$ {
+            type Output = Self;        
+            #[inline(always)]
+            fn bitxor(self, rhs: Self) -> Self::Output { self.symmetric_difference(rhs) }            
+        }"#);
+
+        // suport for bitxorassign '^=' operations
+        self.output.push_str(
+            r#"
+        impl std::ops::BitXorAssign for $$(NAME)$$ {   
+            #[inline(always)]
+            fn bitxor_assign(&mut self, rhs: Self)  { self.value ^= rhs.value; }            
+        }"#,
+        );
+
+        // suport for sub '-' operations (difference)
+        self.output.push_str(r#"
+        impl std::ops::Sub for $$(NAME)$$ {
+            type Output = Self;        
+            #[inline(always)]
+            fn sub(self, rhs: Self) -> Self::Output { self.difference(rhs) }            
+        }"#);
+
+        // suport for subassign '-=' operations
         self.output.push_str(
             r#"
-        impl std::cmp::Eq for $$(NAME)$$ { }
-        impl std::cmp::PartialEq for $$(NAME)$$ {   
+        impl std::ops::SubAssign for $$(NAME)$$ {   
             #[inline(always)]
-            fn eq(&self, other: &Self) -> bool  { self.value == other.value }            
+            fn sub_assign(&mut self, rhs: Self)  { self.value &= !rhs.value; }            
         }"#,
         );
 
+        // suport for not '!' operations (complement)
+        self.output.push_str(r#"
+        impl std::ops::Not for $$(NAME)$$ {
+            type Output = Self;        
+            #[inline(always)]
+            fn not(self) -> Self::Output { self.complement() }            
+        }"#);
+
         // suport default
         self.output.push_str(
             r#"
         impl std::default::Default for $$(NAME)$$ {
-            fn default() -> Self { $$(NAME)$$ { value: 0 } }
+            fn default() -> Self { $$(NAME)$$ { value: $$(ZERO)$$ } }
         }"#,
         );
 
         // suport for Display
         self.output.push_str(
             r#"
+        // the alternate form (`{:#}`) prints only the flags (e.g. `A | B`), without the name of the type
         impl std::fmt::Display for $$(NAME)$$ {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                write!(f, "$$(NAME)$$ (")?;
-                if self.value == 0 {
-                    write!(f,"$$(EMPTY)$$)")?;
+                let alternate = f.alternate();
+                if !alternate {
+                    write!(f, "$$(NAME)$$ (")?;
+                }
+                if self.value == $$(ZERO)$$ {
+                    write!(f,"$$(EMPTY_TEXT)$$")?;
                 } else {
                     let mut first = true;
-                "#,
+                    let mut shown = $$(ZERO)$$;
+                    for (name, flag) in self.iter_names() {
+                        if !first { write!(f," | ")?; } else { first = false; };
+                        write!(f, "{}", name)?;
+                        shown |= flag.value;
+                    }
+                    for text in self.__field_texts() {
+                        if !first { write!(f," | ")?; } else { first = false; };
+                        write!(f, "{}", text)?;
+                    }
+                    // the bits that are not part of the names written above (unknown bits or partially set multi-bit flags);
+                    // they are always written if nothing else was, so that a non-empty value is never shown as empty
+                    let remainder = self.value & !(shown | $$(NAME)$$::__FIELD_BITS);
+                    $$(DISPLAY_UNKNOWN)$$
+                }
+                if !alternate {
+                    write!(f,")")?;
+                }
+                Ok(())            
+            }
+        }
+        "#,
         );
-        self.output.push_str("\n");
-        // sort all items
-        let mut enum_variants: Vec<(&u128, &String)> = self.map_values.iter().collect();
-        enum_variants.sort_by(|e1, e2| e1.1.cmp(e2.1));
-        for (value, name) in enum_variants {
-            self.output.push_str("\t\tif (self.value & ");
-            self.output
-                .push_str(&format!("0x{}{}", value, self.args.flags_type.as_str()));
-            self.output.push_str(") == ");
-            self.output
-                .push_str(&format!("0x{}{}", value, self.args.flags_type.as_str()));
+
+        // suport for Debug: `Name(A | B)` or, for `{:#?}`, the list of flags, the value and the unknown bits
+        if !self.args.derive_debug {
             self.output.push_str(
-                " { if !first { write!(f,\" | \")?; } else { first = false; }; write!(f, \"",
+                r#"
+        impl std::fmt::Debug for $$(NAME)$$ {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let unknown = self.unknown_bits();
+                if f.alternate() {
+                    let mut flags: Vec<String> = self.iter_names().map(|(name, _)| String::from(name)).collect();
+                    flags.extend(self.__field_texts());
+                    return f.debug_struct("$$(NAME)$$")
+                        .field("flags", &flags)
+                        .field("value", &format_args!("0x{:X}", self.value))
+                        .field("unknown_bits", &format_args!("0x{:X}", unknown))
+                        .finish();
+                }
+                write!(f, "$$(NAME)$$(")?;
+                let mut first = true;
+                let mut shown = $$(ZERO)$$;
+                for (name, flag) in self.iter_names() {
+                    if !first { write!(f," | ")?; } else { first = false; };
+                    write!(f, "{}", name)?;
+                    shown |= flag.value;
+                }
+                for text in self.__field_texts() {
+                    if !first { write!(f," | ")?; } else { first = false; };
+                    write!(f, "{}", text)?;
+                }
+                // unknown bits and the bits of partially set multi-bit flags
+                let remainder = self.value & !(shown | $$(NAME)$$::__FIELD_BITS);
+                if remainder != $$(ZERO)$$ {
+                    if !first { write!(f," | ")?; } else { first = false; };
+                    write!(f, "0x{:X}", remainder)?;
+                }
+                if first {
+                    write!(f, "$$(EMPTY_TEXT)$$")?;
+                }
+                write!(f, ")")
+            }
+        }"#,
             );
-            self.output.push_str(name);
-            self.output.push_str("\")?; }\n");
         }
+
+        // suport for LowerHex, UpperHex, Binary and Octal (the value is formatted)
+        for format in ["LowerHex", "UpperHex", "Binary", "Octal"] {
+            self.output.push_str(
+                r#"
+        impl std::fmt::$$(FORMAT)$$ for $$(NAME)$$ {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::$$(FORMAT)$$::fmt(&self.value, f)
+            }
+        }"#
+                .replace("$$(FORMAT)$$", format)
+                .as_str(),
+            );
+        }
+    }
+    // table with the non-empty flags (sorted by name) used by the iterators and by `Display`
+    fn add_flags_table(&mut self) {
+        let mut sorted: Vec<usize> = (0..self.variants.len()).filter(|i| (!self.variants[*i].value.is_zero()) && (!self.variants[*i].is_field())).collect();
+        sorted.sort_by(|i1, i2| self.variants[*i1].name.cmp(&self.variants[*i2].name));
+        self.output.push_str(&format!("\tconst __NAMED_FLAGS: [(&'static str, $$(NAME)$$); {}] = [\n", sorted.len()));
+        for index in sorted.iter() {
+            let variant = &self.variants[*index];
+            self.output.push_str(&format!("\t\t(\"{}\", $$(NAME)$$ {{ value: {} }}),\n", variant.name, self.conditional_literal(variant)));
+        }
+        self.output.push_str("\t];\n");
+        // multi-bit variants (composites) are shown instead of the flags they contain;
+        // the ones with more bits are chosen first and two chosen composites never overlap
+        let mut composites: Vec<usize> = (0..sorted.len()).filter(|i| self.variants[sorted[*i]].is_multi_bit()).collect();
+        composites.sort_by_key(|i| (std::cmp::Reverse(self.variants[sorted[*i]].value.count_ones()), sorted[*i]));
+        let composites: Vec<String> = composites.iter().map(|i| i.to_string()).collect();
+        self.output.push_str(&format!("\tconst __COMPOSITE_FLAGS: [usize; {}] = [{}];\n", composites.len(), composites.join(", ")));
+        self.flags_count = sorted.len();
+    }
+    pub fn add_iterators(&mut self) {
         self.output.push_str(
             r#"
-                    write!(f,")")?;
+        /// An iterator over the names and values of the flags set in a `$$(NAME)$$` object (see `$$(NAME)$$::iter_names`).
+        #[derive(Clone)]
+        $$(VISIBILITY)$$ struct $$(NAME)$$IterNames {
+            value: $$(BITS)$$,
+            covered: $$(BITS)$$,
+            chosen: [bool; $$(FLAGS_COUNT)$$],
+            index: usize,
+        }
+        impl $$(NAME)$$IterNames {
+            $$(CONST)$$fn new(value: $$(BITS)$$) -> Self {
+                let mut chosen = [false; $$(FLAGS_COUNT)$$];
+                let mut covered: $$(BITS)$$ = $$(ZERO)$$;
+                let mut position = 0;
+                while position < $$(NAME)$$::__COMPOSITE_FLAGS.len() {
+                    let index = $$(NAME)$$::__COMPOSITE_FLAGS[position];
+                    let mask = $$(NAME)$$::__NAMED_FLAGS[index].1.value;
+                    if (mask != $$(ZERO)$$) && ((value & mask) == mask) && ((covered & mask) == $$(ZERO)$$) {
+                        chosen[index] = true;
+                        covered |= mask;
+                    }
+                    position += 1;
                 }
-                Ok(())            
+                $$(NAME)$$IterNames { value, covered, chosen, index: 0 }
+            }
+        }
+        impl std::iter::Iterator for $$(NAME)$$IterNames {
+            type Item = (&'static str, $$(NAME)$$);
+            fn next(&mut self) -> Option<Self::Item> {
+                while self.index < $$(FLAGS_COUNT)$$ {
+                    let index = self.index;
+                    self.index += 1;
+                    let (name, flag) = $$(NAME)$$::__NAMED_FLAGS[index];
+                    if flag.value.count_ones() > 1 {
+                        if self.chosen[index] {
+                            return Some((name, flag));
+                        }
+                    } else if (flag.value != $$(ZERO)$$) && ((self.value & flag.value) == flag.value) && ((self.covered & flag.value) == $$(ZERO)$$) {
+                        // a single bit flag (or an alias of it) is only returned once
+                        // (flags whose #[cfg] attributes are not satisfied have the value 0 and are skipped)
+                        self.covered |= flag.value;
+                        return Some((name, flag));
+                    }
+                }
+                None
+            }
+        }
+        /// An iterator over the flags set in a `$$(NAME)$$` object (see `$$(NAME)$$::iter`).
+        #[derive(Clone)]
+        $$(VISIBILITY)$$ struct $$(NAME)$$Iter {
+            inner: $$(NAME)$$IterNames,
+        }
+        impl std::iter::Iterator for $$(NAME)$$Iter {
+            type Item = $$(NAME)$$;
+            #[inline(always)]
+            fn next(&mut self) -> Option<Self::Item> {
+                self.inner.next().map(|(_, flag)| flag)
+            }
+        }
+        impl std::iter::IntoIterator for $$(NAME)$$ {
+            type Item = $$(NAME)$$;
+            type IntoIter = $$(NAME)$$Iter;
+            #[inline(always)]
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+        "#,
+        );
+    }
+    // the type used to store the value of flags wider than 128 bits: an array of u64 words
+    // (least significant word first) with the operators and methods of the primitive types
+    pub fn add_wide_value(&mut self) {
+        let words = match self.args.flags_type {
+            FlagsType::Words(words) => words,
+            _ => return,
+        };
+        let template = r#"
+        /// The value of a `$$(NAME)$$` object: $$(WORDS)$$ words of 64 bits (least significant word first).
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        #[repr(transparent)]
+        $$(VISIBILITY)$$ struct $$(NAME)$$Value(pub [u64; $$(WORDS)$$]);
+        impl $$(NAME)$$Value {
+            /// A value with no bits set.
+            $$(VISIBILITY)$$ const ZERO: $$(NAME)$$Value = $$(NAME)$$Value([0; $$(WORDS)$$]);
+            // the `|` operator for `const` contexts (used by the masks of the flags with #[cfg] attributes)
+            #[allow(dead_code)]
+            const fn __or(self, other: $$(NAME)$$Value) -> $$(NAME)$$Value {
+                let mut words = self.0;
+                let mut index = 0;
+                while index < $$(WORDS)$$ {
+                    words[index] |= other.0[index];
+                    index += 1;
+                }
+                $$(NAME)$$Value(words)
+            }
+            /// Creates a value from an array of words (least significant word first).
+            #[inline(always)]
+            $$(VISIBILITY)$$ const fn from_words(words: [u64; $$(WORDS)$$]) -> $$(NAME)$$Value {
+                $$(NAME)$$Value(words)
+            }
+            /// Creates a value from a slice of words (least significant word first). Missing words are
+            /// considered 0. Returns None if the slice has more than $$(WORDS)$$ words and the extra words are not 0.
+            $$(VISIBILITY)$$ fn from_slice(words: &[u64]) -> Option<$$(NAME)$$Value> {
+                let mut result = [0u64; $$(WORDS)$$];
+                for (index, word) in words.iter().enumerate() {
+                    if index < $$(WORDS)$$ {
+                        result[index] = *word;
+                    } else if *word != 0 {
+                        return None;
+                    }
+                }
+                Some($$(NAME)$$Value(result))
+            }
+            /// Returns the words of the value (least significant word first).
+            #[inline(always)]
+            $$(VISIBILITY)$$ const fn words(&self) -> [u64; $$(WORDS)$$] {
+                self.0
+            }
+            /// Returns the number of bits that are set.
+            $$(VISIBILITY)$$ const fn count_ones(&self) -> u32 {
+                let mut count = 0;
+                let mut index = 0;
+                while index < $$(WORDS)$$ {
+                    count += self.0[index].count_ones();
+                    index += 1;
+                }
+                count
+            }
+            /// Converts a string with digits in the specified radix (2 to 16) into a value.
+            $$(VISIBILITY)$$ fn from_str_radix(text: &str, radix: u32) -> Result<$$(NAME)$$Value, $$(NAME)$$ParseError> {
+                let error = || $$(NAME)$$ParseError { token: String::from(text) };
+                if text.is_empty() || !(2..=16).contains(&radix) {
+                    return Err(error());
+                }
+                let mut result = [0u64; $$(WORDS)$$];
+                for ch in text.chars() {
+                    let mut carry = ch.to_digit(radix).ok_or_else(error)? as u128;
+                    for word in result.iter_mut() {
+                        let value = (*word as u128) * (radix as u128) + carry;
+                        *word = value as u64;
+                        carry = value >> 64;
+                    }
+                    if carry != 0 {
+                        return Err(error());
+                    }
+                }
+                Ok($$(NAME)$$Value(result))
+            }
+        }
+        impl std::default::Default for $$(NAME)$$Value {
+            fn default() -> Self { $$(NAME)$$Value::ZERO }
+        }
+        impl std::str::FromStr for $$(NAME)$$Value {
+            type Err = $$(NAME)$$ParseError;
+            fn from_str(text: &str) -> Result<Self, Self::Err> {
+                $$(NAME)$$Value::from_str_radix(text, 10)
+            }
+        }
+        impl std::cmp::Ord for $$(NAME)$$Value {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.0.iter().rev().cmp(other.0.iter().rev())
+            }
+        }
+        impl std::cmp::PartialOrd for $$(NAME)$$Value {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> { Some(self.cmp(other)) }
+        }
+        impl $$(NAME)$$Value {
+            // writes the value in a base that is a power of 2 (`shift` bits for every digit)
+            fn __fmt_radix(&self, f: &mut std::fmt::Formatter<'_>, shift: usize, prefix: &str, upper: bool) -> std::fmt::Result {
+                let bits: usize = $$(WORDS)$$ * 64;
+                let mut text = String::new();
+                for digit in (0..bits.div_ceil(shift)).rev() {
+                    let mut value = 0u32;
+                    for bit in (digit * shift..(digit + 1) * shift).rev() {
+                        value <<= 1;
+                        if (bit < bits) && ((self.0[bit / 64] >> (bit % 64)) & 1 == 1) {
+                            value |= 1;
+                        }
+                    }
+                    if (value != 0) || (!text.is_empty()) || (digit == 0) {
+                        let ch = std::char::from_digit(value, 1 << shift).unwrap();
+                        text.push(if upper { ch.to_ascii_uppercase() } else { ch });
+                    }
+                }
+                f.pad_integral(true, prefix, &text)
+            }
+        }
+        impl std::fmt::UpperHex for $$(NAME)$$Value {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { self.__fmt_radix(f, 4, "0x", true) }
+        }
+        impl std::fmt::LowerHex for $$(NAME)$$Value {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { self.__fmt_radix(f, 4, "0x", false) }
+        }
+        impl std::fmt::Octal for $$(NAME)$$Value {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { self.__fmt_radix(f, 3, "0o", false) }
+        }
+        impl std::fmt::Binary for $$(NAME)$$Value {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { self.__fmt_radix(f, 1, "0b", false) }
+        }
+        impl std::fmt::Display for $$(NAME)$$Value {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "0x{:X}", self)
+            }
+        }
+        impl std::ops::BitOr for $$(NAME)$$Value {
+            type Output = Self;
+            fn bitor(mut self, rhs: Self) -> Self { self |= rhs; self }
+        }
+        impl std::ops::BitOrAssign for $$(NAME)$$Value {
+            fn bitor_assign(&mut self, rhs: Self) { for index in 0..$$(WORDS)$$ { self.0[index] |= rhs.0[index]; } }
+        }
+        impl std::ops::BitAnd for $$(NAME)$$Value {
+            type Output = Self;
+            fn bitand(mut self, rhs: Self) -> Self { self &= rhs; self }
+        }
+        impl std::ops::BitAndAssign for $$(NAME)$$Value {
+            fn bitand_assign(&mut self, rhs: Self) { for index in 0..$$(WORDS)$$ { self.0[index] &= rhs.0[index]; } }
+        }
+        impl std::ops::BitXor for $$(NAME)$$Value {
+            type Output = Self;
+            fn bitxor(mut self, rhs: Self) -> Self { self ^= rhs; self }
+        }
+        impl std::ops::BitXorAssign for $$(NAME)$$Value {
+            fn bitxor_assign(&mut self, rhs: Self) { for index in 0..$$(WORDS)$$ { self.0[index] ^= rhs.0[index]; } }
+        }
+        impl std::ops::Not for $$(NAME)$$Value {
+            type Output = Self;
+            fn not(mut self) -> Self { for word in self.0.iter_mut() { *word = !*word; } self }
+        }
+        impl std::ops::Shl<u32> for $$(NAME)$$Value {
+            type Output = Self;
+            fn shl(self, amount: u32) -> Self {
+                let mut result = [0u64; $$(WORDS)$$];
+                let (words, bits) = ((amount / 64) as usize, amount % 64);
+                for index in words..$$(WORDS)$$ {
+                    result[index] = self.0[index - words] << bits;
+                    if (bits != 0) && (index > words) {
+                        result[index] |= self.0[index - words - 1] >> (64 - bits);
+                    }
+                }
+                $$(NAME)$$Value(result)
+            }
+        }
+        impl std::ops::Shr<u32> for $$(NAME)$$Value {
+            type Output = Self;
+            fn shr(self, amount: u32) -> Self {
+                let mut result = [0u64; $$(WORDS)$$];
+                let (words, bits) = ((amount / 64) as usize, amount % 64);
+                for index in 0..$$(WORDS)$$usize.saturating_sub(words) {
+                    result[index] = self.0[index + words] >> bits;
+                    if (bits != 0) && (index + words + 1 < $$(WORDS)$$) {
+                        result[index] |= self.0[index + words + 1] << (64 - bits);
+                    }
+                }
+                $$(NAME)$$Value(result)
+            }
+        }
+        impl $$(NAME)$$ {
+            /// Creates a new $$(NAME)$$ object from an array of words (least significant word first).
+            /// This method returns None if the words contain bits that do not belong to any flag (the same as `from_value`).
+            #[inline(always)]
+            $$(VISIBILITY)$$ fn from_words(words: [u64; $$(WORDS)$$]) -> Option<$$(NAME)$$> {
+                $$(NAME)$$::from_value($$(NAME)$$Value(words))
+            }
+            /// Creates a new $$(NAME)$$ object from a slice of words (least significant word first, missing words are considered 0).
+            /// This method returns None if the words contain bits that do not belong to any flag (the same as `from_value`).
+            $$(VISIBILITY)$$ fn from_slice(words: &[u64]) -> Option<$$(NAME)$$> {
+                $$(NAME)$$::from_value($$(NAME)$$Value::from_slice(words)?)
+            }
+        }
+        "#;
+        self.output.push_str(template.replace("$$(WORDS)$$", words.to_string().as_str()).as_str());
+        if self.args.serde_format == SerdeFormat::Disabled {
+            return;
+        }
+        // the numeric encoding of wide values is an array of words
+        self.output.push_str(
+            r#"
+        impl serde::Serialize for $$(NAME)$$Value {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeSeq;
+                let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+                for word in self.0.iter() {
+                    seq.serialize_element(word)?;
+                }
+                seq.end()
+            }
+        }
+        impl<'de> serde::Deserialize<'de> for $$(NAME)$$Value {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let words = <Vec<u64> as serde::Deserialize>::deserialize(deserializer)?;
+                $$(NAME)$$Value::from_slice(&words).ok_or_else(|| serde::de::Error::custom("too many bits for $$(NAME)$$"))
             }
         }
         "#,
         );
     }
+    pub fn add_validation(&mut self) {
+        let mut exclusive = String::new();
+        let mut requires = String::new();
+        let mut exclusive_count = 0;
+        let mut requires_count = 0;
+        for constraint in self.constraints.iter() {
+            let flags: Vec<String> = constraint.flags.iter().map(|index| format!("(\"{}\", {})", self.variants[*index].name, self.literal(self.variants[*index].value))).collect();
+            match constraint.kind {
+                ConstraintKind::Exclusive => {
+                    exclusive.push_str(&format!("&[{}], ", flags.join(", ")));
+                    exclusive_count += 1;
+                }
+                ConstraintKind::Requires => {
+                    requires.push_str(&format!("({}, &[{}]), ", flags[0], flags[1..].join(", ")));
+                    requires_count += 1;
+                }
+            }
+        }
+        self.output.push_str(
+            r#"
+        /// The error returned by `$$(NAME)$$::validate()` and `$$(NAME)$$::try_from_value()`.
+        #[derive(Clone,Copy,Debug,PartialEq,Eq)]
+        $$(VISIBILITY)$$ enum $$(NAME)$$ViolationError {
+            /// The value contains bits that do not belong to any flag.
+            UnknownBits($$(BITS)$$),
+            /// The value is 0 and the empty value is disabled (`disable_empty_generation`).
+            Empty,
+            /// Two flags from the same `exclusive` attribute are set.
+            Exclusive { first: &'static str, second: &'static str },
+            /// A flag from a `requires` attribute is set, but one of the flags it requires is not.
+            Requires { flag: &'static str, missing: &'static str },
+        }
+        impl std::fmt::Display for $$(NAME)$$ViolationError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    $$(NAME)$$ViolationError::UnknownBits(bits) => write!(f, "unknown bits 0x{:X} for $$(NAME)$$", bits),
+                    $$(NAME)$$ViolationError::Empty => write!(f, "the empty value is not allowed for $$(NAME)$$"),
+                    $$(NAME)$$ViolationError::Exclusive { first, second } => write!(f, "flags `{}` and `{}` can not be set at the same time", first, second),
+                    $$(NAME)$$ViolationError::Requires { flag, missing } => write!(f, "flag `{}` requires flag `{}`", flag, missing),
+                }
+            }
+        }
+        impl std::error::Error for $$(NAME)$$ViolationError {}
+        impl $$(NAME)$$ {
+            // the flags from every `exclusive` attribute
+            const __EXCLUSIVE: [&'static [(&'static str, $$(BITS)$$)]; $$(EXCLUSIVE_COUNT)$$] = [$$(EXCLUSIVE)$$];
+            // the flag and the flags it requires from every `requires` attribute
+            const __REQUIRES: [((&'static str, $$(BITS)$$), &'static [(&'static str, $$(BITS)$$)]); $$(REQUIRES_COUNT)$$] = [$$(REQUIRES)$$];
+            /// Checks the constraints declared through the `exclusive` and `requires` attributes.
+            ///
+            /// # Example
+            /// ```rust
+            /// use EnumBitFlags::EnumBitFlags;
+            ///
+            /// #[EnumBitFlags]
+            /// #[exclusive(Compressed, Raw)]
+            /// enum Format { Compressed = 1, Raw = 2, Signed = 4 }
+            ///
+            /// assert!((Format::Compressed | Format::Signed).validate().is_ok());
+            /// assert!((Format::Compressed | Format::Raw).validate().is_err());
+            /// ```
+            $$(VISIBILITY)$$ $$(CONST)$$fn validate(&self) -> Result<(), $$(NAME)$$ViolationError> {
+                // written with `while` loops so that it can be a `const fn`
+                let mut index = 0;
+                while index < $$(NAME)$$::__EXCLUSIVE.len() {
+                    let flags = $$(NAME)$$::__EXCLUSIVE[index];
+                    let mut first: Option<&'static str> = None;
+                    let mut position = 0;
+                    while position < flags.len() {
+                        let (name, flag) = flags[position];
+                        if (self.value & flag) == flag {
+                            if let Some(first) = first {
+                                return Err($$(NAME)$$ViolationError::Exclusive { first, second: name });
+                            }
+                            first = Some(name);
+                        }
+                        position += 1;
+                    }
+                    index += 1;
+                }
+                let mut index = 0;
+                while index < $$(NAME)$$::__REQUIRES.len() {
+                    let ((name, flag), required) = $$(NAME)$$::__REQUIRES[index];
+                    if (self.value & flag) == flag {
+                        let mut position = 0;
+                        while position < required.len() {
+                            let (missing, required) = required[position];
+                            if (self.value & required) != required {
+                                return Err($$(NAME)$$ViolationError::Requires { flag: name, missing });
+                            }
+                            position += 1;
+                        }
+                    }
+                    index += 1;
+                }
+                Ok(())
+            }
+            /// Creates a new $$(NAME)$$ object from an $$(BITS)$$ value, checking both that the value contains
+            /// only known bits (like `from_value`) and that the constraints of the enum are respected (see `validate`).
+            $$(VISIBILITY)$$ $$(CONST)$$fn try_from_value(value: $$(BITS)$$) -> Result<$$(NAME)$$, $$(NAME)$$ViolationError> {
+                match $$(NAME)$$::from_value(value) {
+                    Some(flags) => match flags.validate() {
+                        Ok(()) => Ok(flags),
+                        Err(error) => Err(error),
+                    },
+                    None if value == $$(ZERO)$$ => Err($$(NAME)$$ViolationError::Empty),
+                    None => Err($$(NAME)$$ViolationError::UnknownBits(value & !$$(NAME)$$::ALL_BITS)),
+                }
+            }
+        }
+        "#
+            .replace("$$(EXCLUSIVE_COUNT)$$", exclusive_count.to_string().as_str())
+            .replace("$$(EXCLUSIVE)$$", exclusive.as_str())
+            .replace("$$(REQUIRES_COUNT)$$", requires_count.to_string().as_str())
+            .replace("$$(REQUIRES)$$", requires.as_str())
+            .as_str(),
+        );
+    }
+    pub fn add_from_str(&mut self) {
+        self.output.push_str(
+            r#"
+        /// The error returned when a string can not be converted into a `$$(NAME)$$` object.
+        #[derive(Clone,Debug,PartialEq,Eq)]
+        $$(VISIBILITY)$$ struct $$(NAME)$$ParseError {
+            token: String,
+        }
+        impl $$(NAME)$$ParseError {
+            /// Returns the part of the string that could not be converted (an empty string if a flag name was expected but not found).
+            $$(VISIBILITY)$$ fn token(&self) -> &str {
+                self.token.as_str()
+            }
+        }
+        impl std::fmt::Display for $$(NAME)$$ParseError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                if self.token.is_empty() {
+                    write!(f, "expecting a flag name for $$(NAME)$$")
+                } else {
+                    write!(f, "unknown flag `{}` for $$(NAME)$$", self.token)
+                }
+            }
+        }
+        impl std::error::Error for $$(NAME)$$ParseError {}
+        impl $$(NAME)$$ {
+            // converts one element of a list (a flag name or a hex value) into a $$(NAME)$$ object
+            fn __parse_token(token: &str) -> Option<$$(NAME)$$> {
+                for (name, flag) in $$(NAME)$$::__NAMED_FLAGS {
+                    // (flags whose #[cfg] attributes are not satisfied have the value 0 and are unknown)
+                    if (name == token) && (flag.value != $$(ZERO)$$) {
+                        return Some(flag);
+                    }
+                }
+                $$(EMPTY_NAME_CHECK)$$
+                if let Some((name, value)) = token.split_once('=') {
+                    let (name, value) = (name.trim(), value.trim());
+                    for (field, start, mask, members) in $$(NAME)$$::__FIELDS {
+                        if field == name {
+                            if let Some((_, member)) = members.iter().find(|(member, _)| *member == value) {
+                                return Some($$(NAME)$$ { value: *member << start });
+                            }
+                            let value = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+                                Some(hex) => $$(PARSE_HEX)$$.ok()?,
+                                None => value.parse::<$$(BITS)$$>().ok()?,
+                            };
+                            if value > (mask >> start) {
+                                return None;
+                            }
+                            return Some($$(NAME)$$ { value: value << start });
+                        }
+                    }
+                    return None;
+                }
+                if let Some(hex) = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")) {
+                    if let Ok(value) = $$(PARSE_HEX)$$ {
+                        $$(PARSE_HEX_RESULT)$$
+                    }
+                }
+                None
+            }
+            /// Converts a list of flag names separated by `|` (for example: `"A | B | C"`) into a $$(NAME)$$ object.
+            /// The name of the empty variant, hex values (for example: `0x05`) and field values (for example: `Priority=3`) are also accepted.
+            /// 
+            /// # Example
+            /// ```rust
+            /// use EnumBitFlags::EnumBitFlags;
+            /// 
+            /// #[EnumBitFlags]
+            /// enum MyFlags { A = 1, B = 2, C = 4 }
+            /// 
+            /// assert_eq!(MyFlags::parse_names("A | C"), Ok(MyFlags::A | MyFlags::C));
+            /// assert!(MyFlags::parse_names("A | D").is_err());
+            /// ```
+            $$(VISIBILITY)$$ fn parse_names(text: &str) -> Result<$$(NAME)$$, $$(NAME)$$ParseError> {
+                let mut value: $$(BITS)$$ = $$(ZERO)$$;
+                for token in text.split('|') {
+                    let token = token.trim();
+                    match $$(NAME)$$::__parse_token(token) {
+                        Some(flag) => value |= flag.value,
+                        None => return Err($$(NAME)$$ParseError { token: String::from(token) }),
+                    }
+                }
+                Ok($$(NAME)$$ { value })
+            }
+        }
+        impl std::str::FromStr for $$(NAME)$$ {
+            type Err = $$(NAME)$$ParseError;
+            /// Accepts the format produced by `Display` (for example: `"$$(NAME)$$ (A | B)"`) as well as a list
+            /// of flag names separated by `|` (see `$$(NAME)$$::parse_names`).
+            fn from_str(text: &str) -> Result<Self, Self::Err> {
+                let text = text.trim();
+                if let Some(inner) = text.strip_prefix("$$(NAME)$$").and_then(|rest| rest.trim_start().strip_prefix('(')).and_then(|rest| rest.strip_suffix(')')) {
+                    return $$(NAME)$$::parse_names(inner);
+                }
+                $$(NAME)$$::parse_names(text)
+            }
+        }
+        "#,
+        );
+    }
+    pub fn add_serde(&mut self) {
+        let serialize = match self.args.serde_format {
+            SerdeFormat::Disabled => {
+                return;
+            }
+            SerdeFormat::Names => {
+                r#"
+                use serde::ser::SerializeSeq;
+                let unknown = self.unknown_bits();
+                let count = self.iter_names().count() + self.__field_texts().count() + if unknown != $$(ZERO)$$ { 1 } else { 0 };
+                let mut seq = serializer.serialize_seq(Some(count))?;
+                for (name, _) in self.iter_names() {
+                    seq.serialize_element(name)?;
+                }
+                for text in self.__field_texts() {
+                    seq.serialize_element(&text)?;
+                }
+                if unknown != $$(ZERO)$$ {
+                    seq.serialize_element(&format!("0x{:X}", unknown))?;
+                }
+                seq.end()
+                "#
+            }
+            SerdeFormat::String => {
+                r#"
+                let unknown = self.unknown_bits();
+                let mut text = String::new();
+                for (name, _) in self.iter_names() {
+                    if !text.is_empty() { text.push_str(" | "); }
+                    text.push_str(name);
+                }
+                for field in self.__field_texts() {
+                    if !text.is_empty() { text.push_str(" | "); }
+                    text.push_str(&field);
+                }
+                if unknown != $$(ZERO)$$ {
+                    if !text.is_empty() { text.push_str(" | "); }
+                    text.push_str(&format!("0x{:X}", unknown));
+                }
+                if text.is_empty() { 
+                    $$(SERDE_EMPTY_NAME)$$ 
+                }
+                serializer.serialize_str(&text)
+                "#
+            }
+            SerdeFormat::Numeric => {
+                "serde::Serialize::serialize(&self.value, serializer)"
+            }
+        };
+        let deserialize = match self.args.serde_format {
+            SerdeFormat::Names => {
+                r#"
+                let names = <Vec<String> as serde::Deserialize>::deserialize(deserializer)?;
+                $$(NAME)$$::__serde_from_tokens(names.iter().map(|name| name.as_str())).map_err(serde::de::Error::custom)
+                "#
+            }
+            SerdeFormat::String => {
+                r#"
+                let text = <String as serde::Deserialize>::deserialize(deserializer)?;
+                $$(NAME)$$::__serde_from_tokens(text.split('|')).map_err(serde::de::Error::custom)
+                "#
+            }
+            _ => {
+                if self.args.serde_lenient {
+                    r#"
+                    let value = <$$(BITS)$$ as serde::Deserialize>::deserialize(deserializer)?;
+                    Ok($$(NAME)$$::from_value_truncate(value))
+                    "#
+                } else {
+                    r#"
+                    let value = <$$(BITS)$$ as serde::Deserialize>::deserialize(deserializer)?;
+                    match $$(NAME)$$::from_value(value) {
+                        Some(flags) => Ok(flags),
+                        None => Err(serde::de::Error::custom(format_args!("invalid value 0x{:X} for $$(NAME)$$", value))),
+                    }
+                    "#
+                }
+            }
+        };
+        self.output.push_str("\nimpl serde::Serialize for $$(NAME)$$ {\n");
+        self.output.push_str("\tfn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {\n");
+        self.output.push_str(serialize);
+        self.output.push_str("\t}\n}\n");
+        self.output.push_str("impl<'de> serde::Deserialize<'de> for $$(NAME)$$ {\n");
+        self.output.push_str("\tfn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {\n");
+        self.output.push_str(deserialize);
+        self.output.push_str("\t}\n}\n");
+        if self.args.serde_format == SerdeFormat::Numeric {
+            return;
+        }
+        // unknown names are either rejected or ignored; hex values with unknown bits are either rejected or
+        // truncated to the known bits (the same way as the lenient numeric format)
+        let (unknown_token, result) = if self.args.serde_lenient {
+            (
+                r#"if let Some(hex) = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")) {
+                            if let Ok(bits) = $$(PARSE_HEX)$$ {
+                                value |= $$(NAME)$$::from_value_truncate(bits).value;
+                            }
+                        }"#,
+                "Ok($$(NAME)$$::from_value_truncate(value))",
+            )
+        } else {
+            (
+                "return Err($$(NAME)$$ParseError { token: String::from(token) })",
+                "$$(NAME)$$::from_value(value).ok_or($$(NAME)$$ParseError { token: String::new() })",
+            )
+        };
+        self.output.push_str(
+            r#"
+        impl $$(NAME)$$ {
+            fn __serde_from_tokens<'a>(tokens: impl Iterator<Item = &'a str>) -> Result<$$(NAME)$$, $$(NAME)$$ParseError> {
+                let mut value: $$(BITS)$$ = $$(ZERO)$$;
+                for token in tokens {
+                    let token = token.trim();
+                    match $$(NAME)$$::__parse_token(token) {
+                        Some(flag) => value |= flag.value,
+                        None => $$(SERDE_UNKNOWN_TOKEN)$$,
+                    }
+                }
+                $$(SERDE_RESULT)$$
+            }
+        }
+        "#
+            .replace("$$(SERDE_UNKNOWN_TOKEN)$$", unknown_token)
+            .replace("$$(SERDE_RESULT)$$", result)
+            .as_str(),
+        );
+    }
     pub fn replace_template_parameters(&mut self) {
+        // these templates contain other template parameters (so they have to be replaced first)
+        if self.args.disable_empty_generation {
+            self.output = self
+                .output
+                .replace("$$(DISABLE_EMPTY_CODE)$$", "if value == $$(ZERO)$$ { return None; };");
+            self.output = self
+                .output
+                .replace("$$(EMPTY_NAME_CHECK)$$", "");
+            self.output = self
+                .output
+                .replace("$$(SERDE_EMPTY_NAME)$$", "");
+            // there is no empty variant, but some methods can still return the value 0
+            self.output = self
+                .output
+                .replace("$$(EMPTY_TEXT)$$", "0x0")
+                .replace("$$(EMPTY_VALUE_DOC)$$", " The result can be 0, a value that `from_value` rejects (the `disable_empty_generation` attribute is set) and that is displayed as `0x0`.");
+        }
+        else {
+            self.output = self
+                .output
+                .replace("$$(DISABLE_EMPTY_CODE)$$", "");
+            self.output = self
+                .output
+                .replace("$$(EMPTY_NAME_CHECK)$$", "if token == \"$$(EMPTY)$$\" { return Some($$(NAME)$$ { value: $$(ZERO)$$ }); }");
+            self.output = self
+                .output
+                .replace("$$(SERDE_EMPTY_NAME)$$", "text.push_str(\"$$(EMPTY)$$\");");
+            self.output = self
+                .output
+                .replace("$$(EMPTY_TEXT)$$", "$$(EMPTY)$$")
+                .replace("$$(EMPTY_VALUE_DOC)$$", "");
+        }
+        // the unknown bits written by `Display` are accepted back by `FromStr`
+        if self.args.display_unknown_bits {
+            self.output = self.output.replace("$$(PARSE_HEX_RESULT)$$", "return Some($$(NAME)$$::from_value_retain(value));");
+        } else {
+            self.output = self.output.replace("$$(PARSE_HEX_RESULT)$$", "return $$(NAME)$$::from_value(value);");
+        }
+        if self.args.display_unknown_bits {
+            self.output = self.output.replace(
+                "$$(DISPLAY_UNKNOWN)$$",
+                r#"if remainder != $$(ZERO)$$ {
+                        if !first { write!(f," | ")?; };
+                        write!(f, "0x{:X}", remainder)?;
+                    }"#,
+            );
+        } else {
+            self.output = self.output.replace(
+                "$$(DISPLAY_UNKNOWN)$$",
+                r#"if first && (remainder != $$(ZERO)$$) {
+                        write!(f, "0x{:X}", remainder)?;
+                    }"#,
+            );
+        }
+        if self.args.auto_imply {
+            self.output = self
+                .output
+                .replace("$$(AUTO_IMPLY)$$", "self.value = self.expand().value;")
+                .replace("$$(AUTO_IMPLY_EXPR)$$", ".expand()");
+        } else {
+            self.output = self
+                .output
+                .replace("$$(AUTO_IMPLY)$$", "")
+                .replace("$$(AUTO_IMPLY_EXPR)$$", "");
+        }
+        // for flags wider than 128 bits these contain $$(NAME)$$ (the type of the value is <Name>Value)
+        if self.args.flags_type.is_wide() {
+            self.output = self
+                .output
+                .replace("$$(ZERO)$$", "$$(NAME)$$Value::ZERO")
+                .replace("$$(CONST)$$", "");
+        } else {
+            self.output = self
+                .output
+                .replace("$$(ZERO)$$", "0")
+                .replace("$$(CONST)$$", "const ");
+        }
+        // hex values (as written by Display) are the bit patterns of the flags, even for signed types
+        if self.args.flags_type.is_signed() {
+            self.output = self
+                .output
+                .replace("$$(PARSE_HEX)$$", format!("{}::from_str_radix(hex, 16).map(|value| value as $$(BITS)$$)", self.args.flags_type.unsigned().as_str()).as_str());
+        } else {
+            self.output = self
+                .output
+                .replace("$$(PARSE_HEX)$$", "$$(BITS)$$::from_str_radix(hex, 16)");
+        }
+        self.output = self
+            .output
+            .replace("$$(BITS)$$", self.args.flags_type.as_str());
+        self.output = self
+            .output
+            .replace("$$(ALL_SET_BITS)$$", self.conditional_mask(self.all_set_bits).as_str());
         self.output = self.output.replace("$$(NAME)$$", self.name.as_str());
         self.output = self
             .output
             .replace("$$(EMPTY)$$", self.args.none_case.as_str());
         self.output = self
             .output
-            .replace("$$(BITS)$$", self.args.flags_type.as_str());
+            .replace("$$(VISIBILITY)$$", self.visibility.as_str());
         self.output = self
             .output
-            .replace("$$(VISIBILITY)$$", self.visibility.as_str());
+            .replace("$$(VARIANTS_COUNT)$$", self.variants_count().as_str());
         self.output = self
             .output
-            .replace("$$(ALL_SET_BITS)$$", self.all_set_bits.to_string().as_str());
-        if self.args.disable_empty_generation {
-            self.output = self
-                .output
-                .replace("$$(DISABLE_EMPTY_CODE)$$", "if value==0 { return None; };")
-        }
-        else {
-            self.output = self
-                .output
-                .replace("$$(DISABLE_EMPTY_CODE)$$", "")
-        }
+            .replace("$$(FLAGS_COUNT)$$", self.flags_count.to_string().as_str());
     }
     pub fn stream(self) -> TokenStream {
         if self.args.debug_mode {
@@ -1,36 +1,4 @@
 // a valid enum should start with a letter or "_" character and should contain letters,numbers of underline character
-
-const LOWER_CASE_TABLE: [u8; 256] = [
-    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
-    26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49,
-    50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 97, 98, 99, 100, 101, 102, 103,
-    104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122,
-    91, 92, 93, 94, 95, 96, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111,
-    112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122, 123, 124, 125, 126, 127, 128, 129, 130,
-    131, 132, 133, 134, 135, 136, 137, 138, 139, 140, 141, 142, 143, 144, 145, 146, 147, 148, 149,
-    150, 151, 152, 153, 154, 155, 156, 157, 158, 159, 160, 161, 162, 163, 164, 165, 166, 167, 168,
-    169, 170, 171, 172, 173, 174, 175, 176, 177, 178, 179, 180, 181, 182, 183, 184, 185, 186, 187,
-    188, 189, 190, 191, 192, 193, 194, 195, 196, 197, 198, 199, 200, 201, 202, 203, 204, 205, 206,
-    207, 208, 209, 210, 211, 212, 213, 214, 215, 216, 217, 218, 219, 220, 221, 222, 223, 224, 225,
-    226, 227, 228, 229, 230, 231, 232, 233, 234, 235, 236, 237, 238, 239, 240, 241, 242, 243, 244,
-    245, 246, 247, 248, 249, 250, 251, 252, 253, 254, 255,
-];
-
-pub fn compute_string_hash(buf: &[u8]) -> u64 {
-    // use FNV algorithm ==> https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function
-    if buf.len() == 0 {
-        return 0;
-    }
-    let mut hash = 0xcbf29ce484222325u64;
-    let mut idx = 0usize;
-    while idx < buf.len() {
-        hash = hash ^ (LOWER_CASE_TABLE[buf[idx] as usize] as u64);
-        //hash = hash * 0x00000100000001B3u64;
-        hash = hash.wrapping_mul(0x00000100000001B3u64);
-        idx += 1;
-    }
-    return hash;
-}
 pub fn validate_enum_variant_name(name: &str) -> bool {
     if name.len() == 0 {
         return false;
@@ -174,12 +142,8 @@ fn text_to_number(value: &[u8], base: u128, convert: fn(u8) -> u8) -> Option<u12
             }
             break;
         }
-        let res = result * base + (v as u128);
-        // check overflow cases
-        if res < result {
-            return None;
-        }
-        result = res;
+        // values that do not fit in 128 bits are reported by the caller
+        result = result.checked_mul(base)?.checked_add(v as u128)?;
         index += 1;
     }
     return Some(result);
@@ -205,3 +169,25 @@ pub fn string_to_number(value: &str) -> Option<u128> {
     }
     return text_to_number(&b, 10, char_to_dec);
 }
+
+// converts a variant name (e.g. `Priority`, `IOMode`, `Tx_Level`) into the name of a method (`priority`, `io_mode`, `tx_level`)
+pub fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::with_capacity(name.len() + 4);
+    for (index, ch) in chars.iter().enumerate() {
+        if ch.is_ascii_uppercase() {
+            if index > 0 {
+                let prev = chars[index - 1];
+                let next_is_lower = (index + 1 < chars.len()) && chars[index + 1].is_ascii_lowercase();
+                // a new word starts after a lowercase letter / digit or at the last uppercase letter of an acronym
+                if prev.is_ascii_lowercase() || prev.is_ascii_digit() || (prev.is_ascii_uppercase() && next_is_lower) {
+                    result.push('_');
+                }
+            }
+            result.push(ch.to_ascii_lowercase());
+        } else {
+            result.push(*ch);
+        }
+    }
+    return result;
+}
@@ -0,0 +1,258 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::*;
+
+// the largest number of bits that can be used to store the flags (bits=4096)
+pub const MAX_BITS: u32 = 4096;
+const WORDS: usize = (MAX_BITS / 64) as usize;
+
+// An unsigned integer (stored as 64 bit words, least significant word first) used at macro time to
+// compute the values of the flags. Operations that would need more than MAX_BITS bits either
+// report an overflow (checked_*) or drop the extra bits (shifts).
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Value {
+    words: [u64; WORDS],
+}
+
+impl Value {
+    pub const ZERO: Value = Value { words: [0; WORDS] };
+
+    // a value with the first `count` bits set
+    pub fn ones(count: u32) -> Value {
+        let mut result = Value::ZERO;
+        for index in 0..(count.min(MAX_BITS) as usize) {
+            result.words[index / 64] |= 1u64 << (index % 64);
+        }
+        return result;
+    }
+    pub fn bit(index: u32) -> Value {
+        let mut result = Value::ZERO;
+        if index < MAX_BITS {
+            result.words[(index / 64) as usize] = 1u64 << (index % 64);
+        }
+        return result;
+    }
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        return self.words.iter().all(|w| *w == 0);
+    }
+    pub fn count_ones(&self) -> u32 {
+        return self.words.iter().map(|w| w.count_ones()).sum();
+    }
+    // the index of the highest bit that is set + 1 (0 for a zero value)
+    pub fn bits(&self) -> u32 {
+        for index in (0..WORDS).rev() {
+            if self.words[index] != 0 {
+                return (index as u32) * 64 + 64 - self.words[index].leading_zeros();
+            }
+        }
+        return 0;
+    }
+    pub fn as_u128(&self) -> Option<u128> {
+        if self.bits() > 128 {
+            return None;
+        }
+        return Some((self.words[0] as u128) | ((self.words[1] as u128) << 64));
+    }
+    // the first `count` words (least significant word first)
+    pub fn words(&self, count: usize) -> &[u64] {
+        return &self.words[..count];
+    }
+    pub fn checked_add(&self, other: Value) -> Option<Value> {
+        let mut result = Value::ZERO;
+        let mut carry = 0u128;
+        for index in 0..WORDS {
+            let sum = (self.words[index] as u128) + (other.words[index] as u128) + carry;
+            result.words[index] = sum as u64;
+            carry = sum >> 64;
+        }
+        if carry != 0 {
+            return None;
+        }
+        return Some(result);
+    }
+    pub fn checked_sub(&self, other: Value) -> Option<Value> {
+        if *self < other {
+            return None;
+        }
+        let mut result = Value::ZERO;
+        let mut borrow = 0u64;
+        for index in 0..WORDS {
+            let (value, b1) = self.words[index].overflowing_sub(other.words[index]);
+            let (value, b2) = value.overflowing_sub(borrow);
+            result.words[index] = value;
+            borrow = (b1 || b2) as u64;
+        }
+        return Some(result);
+    }
+    pub fn checked_mul(&self, other: Value) -> Option<Value> {
+        let mut result = [0u64; WORDS];
+        for i in 0..WORDS {
+            if self.words[i] == 0 {
+                continue;
+            }
+            let mut carry = 0u128;
+            for j in 0..WORDS {
+                if i + j >= WORDS {
+                    if (other.words[j] != 0) || (carry != 0) {
+                        return None;
+                    }
+                    continue;
+                }
+                let product = (self.words[i] as u128) * (other.words[j] as u128) + (result[i + j] as u128) + carry;
+                result[i + j] = product as u64;
+                carry = product >> 64;
+            }
+            if carry != 0 {
+                return None;
+            }
+        }
+        return Some(Value { words: result });
+    }
+    // the two's complement of the value, limited to the bits from `mask`
+    pub fn wrapping_neg(&self, mask: Value) -> Value {
+        return (!*self & mask).checked_add(Value::from(1)).unwrap_or(Value::ZERO) & mask;
+    }
+    // (quotient, remainder) or None for a division by zero
+    pub fn div_rem(&self, other: Value) -> Option<(Value, Value)> {
+        if other.is_zero() {
+            return None;
+        }
+        let mut quotient = Value::ZERO;
+        let mut remainder = Value::ZERO;
+        for index in (0..self.bits()).rev() {
+            remainder = remainder << 1;
+            if (self.words[(index / 64) as usize] >> (index % 64)) & 1 != 0 {
+                remainder.words[0] |= 1;
+            }
+            if remainder >= other {
+                remainder = remainder.checked_sub(other).unwrap();
+                quotient.words[(index / 64) as usize] |= 1u64 << (index % 64);
+            }
+        }
+        return Some((quotient, remainder));
+    }
+}
+
+impl From<u128> for Value {
+    fn from(value: u128) -> Value {
+        let mut result = Value::ZERO;
+        result.words[0] = value as u64;
+        result.words[1] = (value >> 64) as u64;
+        return result;
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Value) -> Ordering {
+        for index in (0..WORDS).rev() {
+            match self.words[index].cmp(&other.words[index]) {
+                Ordering::Equal => continue,
+                result => return result,
+            }
+        }
+        return Ordering::Equal;
+    }
+}
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl BitOr for Value {
+    type Output = Value;
+    fn bitor(mut self, other: Value) -> Value {
+        self |= other;
+        return self;
+    }
+}
+impl BitOrAssign for Value {
+    fn bitor_assign(&mut self, other: Value) {
+        for index in 0..WORDS {
+            self.words[index] |= other.words[index];
+        }
+    }
+}
+impl BitAnd for Value {
+    type Output = Value;
+    fn bitand(mut self, other: Value) -> Value {
+        self &= other;
+        return self;
+    }
+}
+impl BitAndAssign for Value {
+    fn bitand_assign(&mut self, other: Value) {
+        for index in 0..WORDS {
+            self.words[index] &= other.words[index];
+        }
+    }
+}
+impl BitXor for Value {
+    type Output = Value;
+    fn bitxor(mut self, other: Value) -> Value {
+        for index in 0..WORDS {
+            self.words[index] ^= other.words[index];
+        }
+        return self;
+    }
+}
+impl Not for Value {
+    type Output = Value;
+    fn not(mut self) -> Value {
+        for index in 0..WORDS {
+            self.words[index] = !self.words[index];
+        }
+        return self;
+    }
+}
+impl Shl<u32> for Value {
+    type Output = Value;
+    fn shl(self, amount: u32) -> Value {
+        let mut result = Value::ZERO;
+        let (words, bits) = ((amount / 64) as usize, amount % 64);
+        for index in (words..WORDS).rev() {
+            let source = index - words;
+            result.words[index] = self.words[source] << bits;
+            if (bits != 0) && (source > 0) {
+                result.words[index] |= self.words[source - 1] >> (64 - bits);
+            }
+        }
+        return result;
+    }
+}
+impl Shr<u32> for Value {
+    type Output = Value;
+    fn shr(self, amount: u32) -> Value {
+        let mut result = Value::ZERO;
+        let (words, bits) = ((amount / 64) as usize, amount % 64);
+        for index in 0..WORDS.saturating_sub(words) {
+            let source = index + words;
+            result.words[index] = self.words[source] >> bits;
+            if (bits != 0) && (source + 1 < WORDS) {
+                result.words[index] |= self.words[source + 1] << (64 - bits);
+            }
+        }
+        return result;
+    }
+}
+
+impl fmt::UpperHex for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.bits().div_ceil(64).max(1) as usize;
+        write!(f, "{:X}", self.words[count - 1])?;
+        for index in (0..count - 1).rev() {
+            write!(f, "{:016X}", self.words[index])?;
+        }
+        return Ok(());
+    }
+}
+// decimal for values that fit in 128 bits, hex otherwise
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.as_u128() {
+            Some(value) => return write!(f, "{}", value),
+            None => return write!(f, "0x{:X}", self),
+        }
+    }
+}
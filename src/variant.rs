@@ -0,0 +1,72 @@
+use super::value::Value;
+use proc_macro::*;
+
+pub struct Variant {
+    pub name: String,
+    pub span: Span,
+    pub attributes: String,
+    pub tokens: Vec<TokenTree>,
+    pub value_span: Span,
+    pub value: Value,
+    pub composite: bool,
+    pub auto: bool,
+    // (first bit, number of bits) for fields
+    pub field: Option<(u32, u32)>,
+    // (name, value relative to the first bit of the group) for groups
+    pub members: Vec<(String, Value)>,
+    // the flags from the `implies` attribute and their indexes (once resolved)
+    pub implies: Vec<Ident>,
+    pub implied: Vec<usize>,
+    // the predicates of the `#[cfg(...)]` attributes of the variant (and the span of the first one)
+    pub cfg: Vec<String>,
+    pub cfg_span: Option<Span>,
+}
+
+impl Variant {
+    pub fn new(name: String, span: Span, attributes: String) -> Variant {
+        Variant {
+            name,
+            span,
+            attributes,
+            tokens: Vec::new(),
+            value_span: span,
+            value: Value::ZERO,
+            composite: false,
+            auto: false,
+            field: None,
+            members: Vec::new(),
+            implies: Vec::new(),
+            implied: Vec::new(),
+            cfg: Vec::new(),
+            cfg_span: None,
+        }
+    }
+    // the span of the value (the first token of the expression)
+    pub fn value_span(&self) -> Span {
+        if let Some(token) = self.tokens.first() {
+            return token.span();
+        }
+        return self.value_span;
+    }
+    #[inline]
+    pub fn is_field(&self) -> bool {
+        return self.field.is_some();
+    }
+    #[inline]
+    pub fn is_group(&self) -> bool {
+        return !self.members.is_empty();
+    }
+    // the condition under which the variant exists (for the `cfg!` macro), None if it has no `#[cfg]` attributes
+    pub fn cfg_condition(&self) -> Option<String> {
+        match self.cfg.len() {
+            0 => return None,
+            1 => return Some(self.cfg[0].clone()),
+            _ => return Some(format!("all({})", self.cfg.join(", "))),
+        }
+    }
+    // a variant that has more than one bit set (either a composite one or a multi-bit mask)
+    #[inline]
+    pub fn is_multi_bit(&self) -> bool {
+        return self.value.count_ones() > 1;
+    }
+}
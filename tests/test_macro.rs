@@ -230,4 +230,797 @@ fn test_new() {
     assert_eq!(Test::from_value(255), None);
     assert_eq!(Test4::from_value(3), Some(Test4::V1 | Test4::V2));
     assert_eq!(Test4::from_value(0), None);
-}
\ No newline at end of file
+    // without an empty variant, the methods that can return 0 are displayed as `0x0`
+    let zero = Test4::from_value_truncate(0x80);
+    assert_eq!(zero.get_value(), 0);
+    assert_eq!(!Test4::all(), zero);
+    assert_eq!(format!("{}", zero), "Test4 (0x0)");
+    assert_eq!(format!("{:?}", zero), "Test4(0x0)");
+    assert!(format!("{}", zero).parse::<Test4>().is_err());
+}
+/// Documented flags (the doc comment is kept on the generated struct)
+#[EnumBitFlags(bits=8)]
+#[derive(Hash, PartialEq)]
+#[allow(dead_code)]
+pub enum TestAttributes {
+    /// First flag
+    V1 = 1,
+    #[allow(non_upper_case_globals)]
+    v2 = 2,
+    #[doc = "Third flag"]
+    V3 = 4,
+    #[cfg(any())]
+    Disabled = 8,
+    #[cfg(all())]
+    Enabled = 16,
+    #[cfg(any())]
+    DisabledComposite = Disabled | V1,
+}
+
+#[EnumBitFlags(bits=256)]
+enum TestAttributesWide {
+    First = 1,
+    #[cfg(any())]
+    Disabled = 1 << 200,
+    #[cfg(all())]
+    Enabled = 1 << 201,
+}
+
+#[test]
+fn test_attributes() {
+    let mut set = std::collections::HashSet::new();
+    set.insert(TestAttributes::V1);
+    set.insert(TestAttributes::V1 | TestAttributes::v2);
+    set.insert(TestAttributes::V1);
+    assert_eq!(set.len(), 2);
+    assert_eq!(TestAttributes::V3.get_value(), 4);
+    assert_eq!(format!("{}", TestAttributes::V1 | TestAttributes::V3), "TestAttributes (V1 | V3)");
+    // a flag removed by #[cfg] is not part of the masks, the count, the iterators and the string conversions
+    assert_eq!(TestAttributes::ALL_BITS, 0x17);
+    assert_eq!(TestAttributes::len(), 4);
+    assert_eq!(TestAttributes::all().iter_names().map(|(name, _)| name).collect::<Vec<_>>(), ["Enabled", "V1", "V3", "v2"]);
+    assert_eq!(TestAttributes::from_value(0x08), None);
+    assert_eq!(format!("{:#}", TestAttributes::from_value_retain(0x19)), "Enabled | V1");
+    assert!("Disabled".parse::<TestAttributes>().is_err());
+    assert!("DisabledComposite".parse::<TestAttributes>().is_err());
+    assert_eq!("Enabled | V1".parse::<TestAttributes>(), Ok(TestAttributes::Enabled | TestAttributes::V1));
+    assert_eq!(!TestAttributes::Enabled, TestAttributes::V1 | TestAttributes::v2 | TestAttributes::V3);
+    assert_eq!(TestAttributesWide::len(), 2);
+    assert_eq!(TestAttributesWide::all(), TestAttributesWide::First | TestAttributesWide::Enabled);
+    assert!("Disabled".parse::<TestAttributesWide>().is_err());
+}
+
+#[EnumBitFlags(bits=8)]
+enum TestComposite {
+    Read = 1,
+    Write = 2,
+    Execute = 4,
+    ReadWrite = Read | Write,
+    All = ReadWrite | Execute,
+    NotRead = !Read,
+    WriteOnly = (All & !Read) & !Execute,
+    High = 0x80,
+}
+
+#[test]
+fn test_composite_values() {
+    assert_eq!(TestComposite::ReadWrite, TestComposite::Read | TestComposite::Write);
+    assert_eq!(TestComposite::All.get_value(), 7);
+    assert_eq!(TestComposite::NotRead.get_value(), 0x86);
+    assert_eq!(TestComposite::WriteOnly, TestComposite::Write);
+    assert!(TestComposite::All.contains(TestComposite::ReadWrite));
+    assert_eq!(TestComposite::from_value(0x87), Some(TestComposite::All | TestComposite::High));
+    assert_eq!(TestComposite::from_value(0x08), None);
+}
+
+#[test]
+fn test_composite_display() {
+    assert_eq!(format!("{}", TestComposite::Read | TestComposite::Write), "TestComposite (ReadWrite)");
+    assert_eq!(format!("{}", TestComposite::All), "TestComposite (All)");
+    assert_eq!(format!("{}", TestComposite::Read | TestComposite::Execute), "TestComposite (Execute | Read)");
+    assert_eq!(format!("{}", TestComposite::Write), "TestComposite (Write)");
+    assert_eq!(format!("{}", TestComposite::All | TestComposite::High), "TestComposite (All | High)");
+    assert_eq!(format!("{}", TestComposite::High), "TestComposite (High)");
+}
+
+#[EnumBitFlags(bits=16)]
+enum TestExpressions {
+    Shift = 1 << 5,
+    Or = 0x10 | 0x08,
+    Mixed = (1 << 2) + 1,
+    High = 0x8000 >> 0,
+    Product = 2 * 3 * 64,
+    Xor = 0x0F00 ^ 0x0300,
+    Masked = !0xFFF7 & 0x00FF,
+}
+
+#[test]
+fn test_expression_values() {
+    assert_eq!(TestExpressions::Shift.get_value(), 0x20);
+    assert_eq!(TestExpressions::Or.get_value(), 0x18);
+    assert_eq!(TestExpressions::Mixed.get_value(), 5);
+    assert_eq!(TestExpressions::High.get_value(), 0x8000);
+    assert_eq!(TestExpressions::Product.get_value(), 384);
+    assert_eq!(TestExpressions::Xor.get_value(), 0x0C00);
+    assert_eq!(TestExpressions::Masked.get_value(), 0x08);
+}
+
+#[EnumBitFlags(bits=8)]
+enum TestAutoBits {
+    A,
+    B = 2,
+    C,
+    D = 0x10,
+    E,
+    AC = A | C,
+    F
+}
+
+#[EnumBitFlags(bits=16, auto_start=8)]
+enum TestAutoStart {
+    A,
+    B = 0x200,
+    C
+}
+
+#[EnumBitFlags(bits=256, auto_start=200)]
+enum TestAutoStartWide {
+    A,
+    B
+}
+
+#[test]
+fn test_auto_bits() {
+    assert_eq!(TestAutoBits::A.get_value(), 0x01);
+    assert_eq!(TestAutoBits::B.get_value(), 0x02);
+    assert_eq!(TestAutoBits::C.get_value(), 0x04);
+    assert_eq!(TestAutoBits::D.get_value(), 0x10);
+    assert_eq!(TestAutoBits::E.get_value(), 0x08);
+    assert_eq!(TestAutoBits::AC.get_value(), 0x05);
+    assert_eq!(TestAutoBits::F.get_value(), 0x20);
+    assert_eq!(TestAutoStart::A.get_value(), 0x100);
+    assert_eq!(TestAutoStartWide::B, TestAutoStartWide::from_slice(&[0, 0, 0, 1 << 9]).unwrap());
+    assert_eq!(TestAutoStart::C.get_value(), 0x400);
+}
+
+#[test]
+fn test_iter() {
+    let t = Test::V1 | Test::V3;
+    let v: Vec<Test> = t.iter().collect();
+    assert_eq!(v, vec![Test::V1, Test::V3]);
+    assert_eq!(Test::None.iter().count(), 0);
+    let mut count = 0;
+    for flag in Test::V1 | Test::V2 | Test::V3 {
+        assert!(flag == Test::V1 || flag == Test::V2 || flag == Test::V3);
+        count += 1;
+    }
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn test_iter_names() {
+    let t = TestComposite::Read | TestComposite::Write | TestComposite::High;
+    let v: Vec<(&str, TestComposite)> = t.iter_names().collect();
+    assert_eq!(v, vec![("High", TestComposite::High), ("ReadWrite", TestComposite::ReadWrite)]);
+    let v: Vec<&str> = TestComposite::Write.iter_names().map(|(name, _)| name).collect();
+    assert_eq!(v, vec!["Write"]);
+    let v: Vec<TestComposite> = (TestComposite::Execute | TestComposite::Read).into_iter().collect();
+    assert_eq!(v, vec![TestComposite::Execute, TestComposite::Read]);
+}
+
+#[test]
+fn test_set_algebra() {
+    let a = Test::V1 | Test::V2;
+    let b = Test::V2 | Test::V3;
+    assert_eq!(a.union(b), Test::V1 | Test::V2 | Test::V3);
+    assert_eq!(a.intersection(b), Test::V2);
+    assert_eq!(a.difference(b), Test::V1);
+    assert_eq!(a.symmetric_difference(b), Test::V1 | Test::V3);
+    assert_eq!(a.complement(), Test::V3);
+    assert_eq!(Test::None.complement(), Test::V1 | Test::V2 | Test::V3);
+    const U: Test = Test::V1.union(Test::V3).difference(Test::V1);
+    assert_eq!(U, Test::V3);
+}
+
+#[test]
+fn test_set_algebra_operators() {
+    let a = Test::V1 | Test::V2;
+    let b = Test::V2 | Test::V3;
+    assert_eq!(a ^ b, Test::V1 | Test::V3);
+    assert_eq!(a - b, Test::V1);
+    assert_eq!(!a, Test::V3);
+    assert_eq!(!Test::None, Test::V1 | Test::V2 | Test::V3);
+    let mut t = a;
+    t ^= b;
+    assert_eq!(t, Test::V1 | Test::V3);
+    t -= Test::V3;
+    assert_eq!(t, Test::V1);
+    t -= Test::V3;
+    assert_eq!(t, Test::V1);
+}
+
+#[test]
+fn test_known_bits() {
+    assert_eq!(Test::ALL_BITS, 0x83);
+    assert_eq!(Test::all(), Test::V1 | Test::V2 | Test::V3);
+    assert!(Test::all().is_all());
+    assert!(!(Test::V1 | Test::V3).is_all());
+    assert_eq!((Test::V1 | Test::V3).count(), 2);
+    assert_eq!(Test::None.count(), 0);
+    assert_eq!((Test::V1 | Test::V3).known_bits(), Test::V1 | Test::V3);
+    assert_eq!((Test::V1 | Test::V3).unknown_bits(), 0);
+    assert_eq!(Test::len(), 3);
+    assert_eq!(TestComposite::len(), 8);
+    assert_eq!(TestComposite::all().count(), 4);
+    const ALL: Test4 = Test4::all();
+    assert_eq!(ALL.get_value(), 7);
+}
+
+#[test]
+fn test_lenient_constructors() {
+    assert_eq!(Test::from_value_truncate(0xFF), Test::V1 | Test::V2 | Test::V3);
+    assert_eq!(Test::from_value_truncate(0x04), Test::None);
+    let t = Test::from_value_retain(0x85);
+    assert!(t.contains(Test::V1 | Test::V3));
+    assert!(t.has_unknown_bits());
+    assert_eq!(t.unknown_bits(), 0x04);
+    assert_eq!(t.known_bits(), Test::V1 | Test::V3);
+    assert_eq!(t.get_value(), 0x85);
+    assert!(!Test::from_value_retain(0x81).has_unknown_bits());
+    let t = unsafe { Test::from_value_unchecked(3) };
+    assert_eq!(t, Test::V1 | Test::V2);
+    const T: Option<Test> = Test::from_value(0x81);
+    assert_eq!(T, Some(Test::V1 | Test::V3));
+    const R: Test = Test::from_value_truncate(0x0F);
+    assert_eq!(R, Test::V1 | Test::V2);
+}
+
+#[test]
+fn test_from_str() {
+    use std::str::FromStr;
+    assert_eq!(Test::from_str("Test (V1 | V2)"), Ok(Test::V1 | Test::V2));
+    assert_eq!(Test::from_str("V1|V3"), Ok(Test::V1 | Test::V3));
+    assert_eq!(Test::from_str(" V3 "), Ok(Test::V3));
+    assert_eq!(Test::from_str("Test (None)"), Ok(Test::None));
+    assert_eq!(Test::from_str("None"), Ok(Test::None));
+    assert_eq!(Test::from_str("0x81"), Ok(Test::V1 | Test::V3));
+    assert_eq!(Test::from_str("V2 | 0x80"), Ok(Test::V2 | Test::V3));
+    assert_eq!("V1 | V2".parse::<Test>(), Ok(Test::V1 | Test::V2));
+    assert_eq!(TestComposite::from_str("ReadWrite | Execute"), Ok(TestComposite::All));
+    for t in [Test::V1, Test::V1 | Test::V3, Test::all(), Test::None] {
+        assert_eq!(Test::from_str(format!("{}", t).as_str()), Ok(t));
+    }
+}
+
+#[test]
+fn test_from_str_errors() {
+    use std::str::FromStr;
+    let err = Test::from_str("V1 | V4").unwrap_err();
+    assert_eq!(err.token(), "V4");
+    assert_eq!(format!("{}", err), "unknown flag `V4` for Test");
+    assert_eq!(Test::from_str("0x04").unwrap_err().token(), "0x04");
+    assert_eq!(Test::from_str("V1 |").unwrap_err().token(), "");
+    assert_eq!(Test::from_str("v1").unwrap_err().token(), "v1");
+    assert_eq!(Test4::from_str("None").unwrap_err().token(), "None");
+    assert_eq!(Test4::from_str("0x0").unwrap_err().token(), "0x0");
+    assert_eq!(Test2::parse_names("NoBitsSet"), Ok(Test2::NoBitsSet));
+}
+
+#[EnumBitFlags(bits=8, serde=names)]
+enum TestSerdeNames {
+    A = 1,
+    B = 2,
+    C = 4,
+    AB = A | B,
+}
+
+#[EnumBitFlags(bits=8, serde=string)]
+enum TestSerdeString {
+    A = 1,
+    B = 2,
+    C = 4,
+}
+
+#[EnumBitFlags(bits=16, serde=numeric)]
+enum TestSerdeNumeric {
+    A = 1,
+    B = 0x100,
+}
+
+#[EnumBitFlags(bits=8, serde=string, serde_lenient=true)]
+enum TestSerdeLenient {
+    A = 1,
+    B = 2,
+}
+
+#[EnumBitFlags(bits=8, serde=numeric, serde_lenient=true)]
+enum TestSerdeLenientNumeric {
+    A = 1,
+    B = 2,
+}
+
+#[test]
+fn test_serde_names() {
+    let t = TestSerdeNames::A | TestSerdeNames::B | TestSerdeNames::C;
+    assert_eq!(serde_json::to_string(&t).unwrap(), r#"["AB","C"]"#);
+    assert_eq!(serde_json::to_string(&TestSerdeNames::None).unwrap(), "[]");
+    assert_eq!(serde_json::to_string(&TestSerdeNames::from_value_retain(0x11)).unwrap(), r#"["A","0x10"]"#);
+    assert_eq!(serde_json::from_str::<TestSerdeNames>(r#"["AB","C"]"#).unwrap(), t);
+    assert_eq!(serde_json::from_str::<TestSerdeNames>(r#"["A","C"]"#).unwrap(), TestSerdeNames::A | TestSerdeNames::C);
+    assert_eq!(serde_json::from_str::<TestSerdeNames>("[]").unwrap(), TestSerdeNames::None);
+    assert!(serde_json::from_str::<TestSerdeNames>(r#"["A","D"]"#).is_err());
+    assert!(serde_json::from_str::<TestSerdeNames>(r#"["0x10"]"#).is_err());
+}
+
+#[test]
+fn test_serde_string() {
+    let t = TestSerdeString::A | TestSerdeString::C;
+    assert_eq!(serde_json::to_string(&t).unwrap(), r#""A | C""#);
+    assert_eq!(serde_json::to_string(&TestSerdeString::None).unwrap(), r#""None""#);
+    assert_eq!(serde_json::from_str::<TestSerdeString>(r#""A | C""#).unwrap(), t);
+    assert_eq!(serde_json::from_str::<TestSerdeString>(r#""None""#).unwrap(), TestSerdeString::None);
+    let err = serde_json::from_str::<TestSerdeString>(r#""A | X""#).unwrap_err();
+    assert!(err.to_string().contains("unknown flag `X` for TestSerdeString"));
+}
+
+#[test]
+fn test_serde_numeric() {
+    let t = TestSerdeNumeric::A | TestSerdeNumeric::B;
+    assert_eq!(serde_json::to_string(&t).unwrap(), "257");
+    assert_eq!(serde_json::from_str::<TestSerdeNumeric>("257").unwrap(), t);
+    assert!(serde_json::from_str::<TestSerdeNumeric>("3").is_err());
+}
+
+#[test]
+fn test_serde_lenient() {
+    assert_eq!(serde_json::from_str::<TestSerdeLenient>(r#""A | X | B""#).unwrap(), TestSerdeLenient::A | TestSerdeLenient::B);
+    assert_eq!(serde_json::from_str::<TestSerdeLenientNumeric>("255").unwrap(), TestSerdeLenientNumeric::A | TestSerdeLenientNumeric::B);
+    // hex values keep their known bits
+    assert_eq!(serde_json::from_str::<TestSerdeLenient>(r#""0x13""#).unwrap(), TestSerdeLenient::A | TestSerdeLenient::B);
+    assert_eq!(serde_json::from_str::<TestSerdeLenient>(r#""0x10 | A""#).unwrap(), TestSerdeLenient::A);
+}
+
+#[EnumBitFlags(bits=8, case_sensitive=true)]
+#[allow(non_upper_case_globals)]
+enum TestCaseSensitive {
+    Ab = 1,
+    AB = 2,
+    ab = 4,
+    Both = Ab | AB,
+}
+
+#[test]
+fn test_case_sensitive() {
+    assert_eq!(TestCaseSensitive::Ab.get_value(), 1);
+    assert_eq!(TestCaseSensitive::AB.get_value(), 2);
+    assert_eq!(TestCaseSensitive::ab.get_value(), 4);
+    assert_eq!(TestCaseSensitive::Both.get_value(), 3);
+    assert_eq!(format!("{}", TestCaseSensitive::AB | TestCaseSensitive::ab), "TestCaseSensitive (AB | ab)");
+    assert_eq!("ab | Ab".parse::<TestCaseSensitive>(), Ok(TestCaseSensitive::Ab | TestCaseSensitive::ab));
+}
+
+#[EnumBitFlags(bits=16)]
+enum TestFields {
+    Valid = 1,
+    Urgent = 2,
+    Priority = field(4..7),
+    ChannelId = field(8..=15),
+}
+
+#[test]
+fn test_fields() {
+    let mut f = TestFields::Valid;
+    assert_eq!(f.priority(), 0);
+    f.set_priority(5);
+    f.set_channel_id(0xAB);
+    assert_eq!(f.priority(), 5);
+    assert_eq!(f.channel_id(), 0xAB);
+    assert_eq!(f.get_value(), 0xAB51);
+    assert!(f.contains(TestFields::Valid));
+    f.set_priority(0);
+    assert_eq!(f.get_value(), 0xAB01);
+    assert_eq!(TestFields::ALL_BITS, 0xFF73);
+    assert!(TestFields::from_value(0x0070).is_some());
+    assert!(TestFields::from_value(0x0080).is_none());
+    assert_eq!(TestFields::from_value(0x0031).unwrap().priority(), 3);
+    // fields are not flags
+    assert_eq!(TestFields::len(), 2);
+    assert_eq!(TestFields::from_value(0xFF73).unwrap().iter().count(), 2);
+    f.set_priority(3);
+    assert_eq!(format!("{}", f), "TestFields (Valid | Priority=3 | ChannelId=171)");
+    assert_eq!(format!("{}", f).parse::<TestFields>(), Ok(f));
+    assert_eq!(TestFields::parse_names("Urgent | Priority=0x7"), Ok(TestFields::from_value(0x0072).unwrap()));
+    assert!(TestFields::parse_names("Priority=8").is_err());
+    // the complement and all() only contain flags (the fields are 0)
+    assert_eq!(!TestFields::Valid, TestFields::Urgent);
+    assert_eq!((!f).priority(), 0);
+    assert_eq!((!f).channel_id(), 0);
+    assert_eq!(format!("{}", !f), "TestFields (Urgent)");
+    assert_eq!(TestFields::all().priority(), 0);
+    assert_eq!(TestFields::all().count(), 2);
+    assert!((TestFields::Valid | TestFields::Urgent).is_all());
+    assert!(!f.is_all());
+}
+
+#[test]
+#[should_panic]
+fn test_field_out_of_range() {
+    let mut f = TestFields::Urgent;
+    f.set_priority(8);
+}
+
+#[EnumBitFlags(bits=8)]
+enum TestGroups {
+    Enabled = 0x01,
+    Mode = group(4..6, Slow = 1, Fast = 2, Turbo = 3),
+    Level = field(6..8),
+}
+
+#[test]
+fn test_groups() {
+    let mut g = TestGroups::Enabled;
+    assert_eq!(g.mode(), None);
+    g.set_mode(TestGroupsMode::Fast);
+    assert_eq!(g.mode(), Some(TestGroupsMode::Fast));
+    assert_eq!(g.get_value(), 0x21);
+    g.set_mode(TestGroupsMode::Slow);
+    assert_eq!(g.mode(), Some(TestGroupsMode::Slow));
+    assert_eq!(g.get_value(), 0x11);
+    g.set_level(2);
+    assert_eq!(format!("{}", g), "TestGroups (Enabled | Mode=Slow | Level=2)");
+    assert_eq!(format!("{}", g).parse::<TestGroups>(), Ok(g));
+    assert_eq!(TestGroups::parse_names("Mode=Turbo").map(|v| v.mode()), Ok(Some(TestGroupsMode::Turbo)));
+    assert!(TestGroups::parse_names("Mode=Medium").is_err());
+    g.clear_mode();
+    assert_eq!(g.get_value(), 0x81);
+    assert_eq!(TestGroups::len(), 1);
+}
+
+#[EnumBitFlags(bits=8)]
+enum TestGroupsZero {
+    Enabled = 0x01,
+    Mode = group(4..6, Off = 0, On = 1),
+}
+
+#[test]
+fn test_groups_zero_member() {
+    let g = TestGroupsZero::Enabled;
+    assert_eq!(g.mode(), Some(TestGroupsZeroMode::Off));
+    assert_eq!(format!("{}", g), "TestGroupsZero (Enabled | Mode=Off)");
+    assert_eq!(format!("{:?}", g), "TestGroupsZero(Enabled | Mode=Off)");
+    assert_eq!(format!("{}", g).parse::<TestGroupsZero>(), Ok(g));
+    assert_eq!(TestGroupsZero::parse_names("Enabled | Mode=On").map(|v| v.get_value()), Ok(0x11));
+}
+
+#[EnumBitFlags(bits=8)]
+enum TestImplies {
+    Read = 1,
+    Write = 2,
+    #[implies(Read)]
+    Delete = 4,
+    #[implies(Write, Delete)]
+    Admin = 8,
+}
+
+#[EnumBitFlags(bits=8, auto_imply=true)]
+enum TestAutoImply {
+    Read = 1,
+    Write = 2,
+    #[implies(Read | Write)]
+    Admin = 4,
+}
+
+#[test]
+fn test_implies() {
+    assert_eq!(TestImplies::Admin.expand().get_value(), 0x0F);
+    assert_eq!(TestImplies::Delete.expand(), TestImplies::Delete | TestImplies::Read);
+    assert_eq!(TestImplies::Write.expand(), TestImplies::Write);
+    assert_eq!((TestImplies::Admin | TestImplies::Read | TestImplies::Write).normalize(), TestImplies::Admin);
+    assert_eq!((TestImplies::Delete | TestImplies::Write).normalize(), TestImplies::Delete | TestImplies::Write);
+    // without auto_imply the implied flags are not added
+    assert_eq!((TestImplies::Admin | TestImplies::Write).get_value(), 0x0A);
+
+    assert_eq!((TestAutoImply::Admin | TestAutoImply::Read).get_value(), 0x07);
+    let mut a = TestAutoImply::Read;
+    a.set(TestAutoImply::Admin);
+    assert_eq!(a.get_value(), 0x07);
+    let mut b = TestAutoImply::Write;
+    b |= TestAutoImply::Admin;
+    assert_eq!(b.get_value(), 0x07);
+    assert_eq!(b.normalize(), TestAutoImply::Admin);
+}
+
+#[EnumBitFlags(bits=8)]
+#[exclusive(Compressed, Raw)]
+#[requires(Signed, Checksum, Header)]
+enum TestConstraints {
+    Compressed = 1,
+    Raw = 2,
+    Signed = 4,
+    Checksum = 8,
+    Header = 16,
+}
+
+#[test]
+fn test_constraints() {
+    assert_eq!(TestConstraints::Compressed.validate(), Ok(()));
+    assert_eq!((TestConstraints::Compressed | TestConstraints::Raw).validate(), Err(TestConstraintsViolationError::Exclusive { first: "Compressed", second: "Raw" }));
+    assert_eq!((TestConstraints::Signed | TestConstraints::Checksum).validate(), Err(TestConstraintsViolationError::Requires { flag: "Signed", missing: "Header" }));
+    assert_eq!((TestConstraints::Signed | TestConstraints::Checksum | TestConstraints::Header).validate(), Ok(()));
+    assert_eq!(TestConstraints::try_from_value(0x1D).map(|f| f.get_value()), Ok(0x1D));
+    assert_eq!(TestConstraints::try_from_value(0x03), Err(TestConstraintsViolationError::Exclusive { first: "Compressed", second: "Raw" }));
+    assert_eq!(TestConstraints::try_from_value(0x41), Err(TestConstraintsViolationError::UnknownBits(0x40)));
+    assert_eq!(format!("{}", TestConstraints::try_from_value(0x04).unwrap_err()), "flag `Signed` requires flag `Checksum`");
+    // enums without constraints are always valid
+    assert_eq!(TestFields::from_value(0xFF73).unwrap().validate(), Ok(()));
+}
+
+#[EnumBitFlags(bits=256)]
+enum TestWide {
+    First = 1,
+    Second = 2,
+    Middle = 1 << 100,
+    High = 1 << 200,
+    Last = 1 << 255,
+    Edges = First | Last,
+    Next,
+}
+
+#[test]
+fn test_wide() {
+    assert_eq!(TestWide::High.get_value().words(), [0, 0, 0, 1 << 8]);
+    assert_eq!(TestWide::Next.get_value().words(), [4, 0, 0, 0]);
+    assert_eq!(TestWide::Middle.get_value(), TestWideValue::from_words([0, 1 << 36, 0, 0]));
+    let mut f = TestWide::First | TestWide::High;
+    assert!(f.contains(TestWide::High));
+    assert!(!f.contains(TestWide::Middle));
+    f.set(TestWide::Middle);
+    f.remove(TestWide::First);
+    assert_eq!(f, TestWide::Middle | TestWide::High);
+    assert_eq!(f.count(), 2);
+    assert_eq!((f ^ TestWide::High), TestWide::Middle);
+    assert_eq!((!f).count(), 4);
+    assert!(TestWide::all().is_all());
+    assert_eq!(TestWide::from_words([1, 0, 0, 1 << 63]), Some(TestWide::Edges));
+    assert_eq!(TestWide::from_words([8, 0, 0, 0]), None);
+    assert_eq!(TestWide::from_slice(&[2]), Some(TestWide::Second));
+    assert_eq!(TestWide::from_slice(&[0, 0, 0, 0, 1]), None);
+    let retained = TestWide::from_value_retain(TestWideValue::from_words([1, 1, 0, 0]));
+    assert_eq!(retained.unknown_bits(), TestWideValue::from_words([0, 1, 0, 0]));
+    assert_eq!(format!("{}", TestWide::First | TestWide::High | TestWide::Last), "TestWide (Edges | High)");
+    assert_eq!(format!("{}", TestWide::None), "TestWide (None)");
+    assert_eq!("Middle | Second".parse::<TestWide>(), Ok(TestWide::Middle | TestWide::Second));
+    assert_eq!(TestWide::parse_names("0x10000000000000000000000000"), Ok(TestWide::Middle));
+    assert_eq!(format!("{:X}", TestWide::Middle.get_value()), "10000000000000000000000000");
+    assert_eq!(TestWide::len(), 7);
+}
+
+#[EnumBitFlags(bits=i8)]
+enum TestSigned {
+    Low = 1,
+    Mid = 0x10,
+    Sign = -128,
+    Both = Low | Sign,
+}
+
+#[EnumBitFlags(bits=usize)]
+enum TestNative {
+    A = 1,
+    B = 1 << 20,
+    C,
+}
+
+#[test]
+fn test_signed() {
+    let v: i8 = TestSigned::Sign.get_value();
+    assert_eq!(v, i8::MIN);
+    assert_eq!(TestSigned::Both.get_value(), -127);
+    assert_eq!(TestSigned::from_value(-112), Some(TestSigned::Mid | TestSigned::Sign));
+    assert_eq!(TestSigned::from_value(2), None);
+    assert_eq!((!TestSigned::Sign), TestSigned::Low | TestSigned::Mid);
+    assert_eq!(TestSigned::all().get_value(), -111);
+    assert_eq!(format!("{}", TestSigned::Low | TestSigned::Sign), "TestSigned (Both)");
+    assert_eq!(TestSigned::parse_names("0x90"), Ok(TestSigned::Mid | TestSigned::Sign));
+    assert_eq!(TestSigned::from_value_retain(-1).unknown_bits(), 0x6E);
+    let n: usize = (TestNative::A | TestNative::C).get_value();
+    assert_eq!(n, 3);
+    assert_eq!(TestNative::B.get_value(), 1usize << 20);
+}
+
+#[EnumBitFlags]
+#[repr(u16)]
+enum TestRepr {
+    A = 1,
+    B = 0x8000,
+}
+
+#[EnumBitFlags(bits=auto)]
+enum TestBitsAuto {
+    A = 1,
+    B = 1 << 12,
+    C,
+}
+
+#[test]
+fn test_repr() {
+    let value: u16 = (TestRepr::A | TestRepr::B).get_value();
+    assert_eq!(value, 0x8001);
+    assert_eq!(std::mem::size_of::<TestRepr>(), 2);
+    assert_eq!(std::mem::align_of::<TestRepr>(), std::mem::align_of::<u16>());
+    let from_ffi: TestRepr = unsafe { std::mem::transmute(0x8000u16) };
+    assert_eq!(from_ffi, TestRepr::B);
+    let value: u16 = TestBitsAuto::all().get_value();
+    assert_eq!(value, 0x1003);
+    assert_eq!(std::mem::size_of::<TestBitsAuto>(), 2);
+}
+
+#[EnumBitFlags(bits=8)]
+#[derive(std::hash::Hash, PartialOrd)]
+enum TestOrder {
+    A = 1,
+    B = 2,
+    C = 4,
+}
+
+#[test]
+fn test_order() {
+    let mut list = vec![TestOrder::C, TestOrder::A | TestOrder::B, TestOrder::A];
+    list.sort();
+    assert_eq!(list, [TestOrder::A, TestOrder::A | TestOrder::B, TestOrder::C]);
+    assert!(TestOrder::B < TestOrder::C);
+    let mut map = std::collections::BTreeMap::new();
+    map.insert(TestOrder::C, "c");
+    map.insert(TestOrder::A, "a");
+    assert_eq!(map.keys().next(), Some(&TestOrder::A));
+    let ab = TestOrder::A | TestOrder::B;
+    assert!(TestOrder::A.is_subset_of(ab));
+    assert!(ab.is_superset_of(TestOrder::B));
+    assert!(!ab.is_subset_of(TestOrder::A));
+    assert!(TestOrder::None.is_subset_of(TestOrder::C));
+    assert!(ab.is_disjoint(TestOrder::C));
+    assert!(!ab.is_disjoint(TestOrder::B | TestOrder::C));
+}
+
+#[EnumBitFlags(bits=8, display_unknown_bits=true)]
+enum TestFormat {
+    A = 0x01,
+    B = 0x20,
+}
+
+#[test]
+fn test_format() {
+    let ab = TestFormat::A | TestFormat::B;
+    assert_eq!(format!("{:x} {:X} {:#x} {:b} {:o} {:08b}", ab, ab, ab, ab, ab, TestFormat::A), "21 21 0x21 100001 41 00000001");
+    assert_eq!(format!("{:#}", ab), "A | B");
+    assert_eq!(format!("{:#}", TestFormat::None), "None");
+    assert_eq!(format!("{}", TestFormat::from_value_retain(0x61)), "TestFormat (A | B | 0x40)");
+    assert_eq!("TestFormat (A | B | 0x40)".parse(), Ok(TestFormat::from_value_retain(0x61)));
+    assert_eq!(TestFormat::parse_names("0x80"), Ok(TestFormat::from_value_retain(0x80)));
+    assert_eq!(format!("{:#}", TestFormat::from_value_retain(0x80)), "0x80");
+    let wide = TestWide::Middle | TestWide::First;
+    assert_eq!(format!("{:x}", wide), "10000000000000000000000001");
+    assert_eq!(format!("{:#b}", TestWide::Next), "0b100");
+    assert_eq!(format!("{:o}", TestWide::Last), format!("1{}", "0".repeat(85)));
+    assert_eq!(format!("{:#}", TestWide::First | TestWide::Second), "First | Second");
+}
+
+#[EnumBitFlags(bits=8)]
+enum TestDisplayRemainder {
+    A = 0x01,
+    Pair = 0x06,
+}
+
+#[test]
+fn test_display_remainder() {
+    // a non-empty value is never shown as empty (even if no flag is fully set or the bits are unknown)
+    let partial = TestDisplayRemainder::from_value(0x02).unwrap();
+    assert_eq!(format!("{}", partial), "TestDisplayRemainder (0x2)");
+    assert_eq!(format!("{:#}", partial), "0x2");
+    assert_eq!(TestDisplayRemainder::parse_names(format!("{:#}", partial).as_str()), Ok(partial));
+    assert_eq!(format!("{:#}", TestDisplayRemainder::from_value_retain(0x10)), "0x10");
+    assert_eq!(format!("{}", TestDisplayRemainder::A | partial), "TestDisplayRemainder (A)");
+    assert_eq!(format!("{}", TestFormat::from_value_retain(0x41)), "TestFormat (A | 0x40)");
+    // with `display_unknown_bits` the unknown bits are printed, so the text is parsed back into the same value
+    for value in [0x41u8, 0x80, 0xC0, 0xFF] {
+        let flags = TestFormat::from_value_retain(value);
+        assert_eq!(format!("{}", flags).parse::<TestFormat>(), Ok(flags));
+        assert_eq!(TestFormat::parse_names(format!("{:#}", flags).as_str()), Ok(flags));
+    }
+}
+
+// the same flags for every representation (`Top` is the highest bit, the sign bit for signed types)
+macro_rules! display_matrix {
+    ($($name:ident: $bits:tt),*) => {
+        $(
+            #[EnumBitFlags(bits=$bits)]
+            enum $name {
+                A = 0x01,
+                B = 0x10,
+                C = 0x0A,
+                D = 0x40,
+                AB = A | B,
+                ABD = A | B | D,
+                Top = !(!0 >> 1),
+            }
+        )*
+        #[test]
+        fn test_display_matrix() {
+            $(
+                assert_eq!(format!("{}", $name::A | $name::B), format!("{} (AB)", stringify!($name)));
+                assert_eq!(format!("{:#}", $name::A | $name::B | $name::D), "ABD");
+                assert_eq!(format!("{:#}", $name::B | $name::D), "B | D");
+                assert_eq!(format!("{:#}", $name::C), "C");
+                assert_eq!(format!("{:#}", $name::C | $name::Top), "C | Top");
+                assert_eq!(format!("{:#}", $name::None), "None");
+                assert_eq!(format!("{:x}", $name::B | $name::C), "1a");
+                assert_eq!(format!("{:#}", $name::all()), "ABD | C | Top");
+                assert_eq!(format!("{}", $name::A | $name::C).parse::<$name>(), Ok($name::A | $name::C));
+            )*
+        }
+    };
+}
+
+display_matrix!(
+    TestDisplay8: 8, TestDisplay16: 16, TestDisplay32: 32, TestDisplay64: 64, TestDisplay128: 128,
+    TestDisplayI8: i8, TestDisplayI16: i16, TestDisplayI32: i32, TestDisplayI64: i64, TestDisplayI128: i128,
+    TestDisplayUsize: usize, TestDisplayIsize: isize, TestDisplay256: 256
+);
+
+#[EnumBitFlags(bits=8)]
+enum TestDebug {
+    A = 0x01,
+    C = 0x04,
+    Level = field(4..6),
+}
+
+#[EnumBitFlags(bits=8, derive_debug=true)]
+#[derive(Debug)]
+enum TestDerivedDebug {
+    A = 1,
+}
+
+#[test]
+fn test_debug() {
+    assert_eq!(format!("{:?}", TestDebug::A | TestDebug::C), "TestDebug(A | C)");
+    assert_eq!(format!("{:?}", TestDebug::None), "TestDebug(None)");
+    assert_eq!(format!("{:?}", TestDebug::from_value_retain(0x81)), "TestDebug(A | 0x80)");
+    assert_eq!(format!("{:?}", TestDisplayRemainder::from_value(0x02).unwrap()), "TestDisplayRemainder(0x2)");
+    assert_eq!(format!("{:?}", TestDisplayRemainder::from_value(0x03).unwrap()), "TestDisplayRemainder(A | 0x2)");
+    let mut flags = TestDebug::C;
+    flags.set_level(2);
+    assert_eq!(format!("{:?}", Some(flags)), "Some(TestDebug(C | Level=2))");
+    assert_eq!(
+        format!("{:#?}", TestDebug::from_value_retain(0x85)),
+        "TestDebug {\n    flags: [\n        \"A\",\n        \"C\",\n    ],\n    value: 0x85,\n    unknown_bits: 0x80,\n}"
+    );
+    assert_eq!(format!("{:?}", TestDerivedDebug::A), "TestDerivedDebug { value: 1 }");
+}
+
+const TEST_CONST_RW: TestOrder = TestOrder::A.or(TestOrder::B);
+static TEST_CONST_TABLE: [TestOrder; 4] = [
+    TEST_CONST_RW,
+    TEST_CONST_RW.and(TestOrder::B),
+    TEST_CONST_RW.xor(TestOrder::C),
+    TEST_CONST_RW.not(),
+];
+const TEST_CONST_EDITED: TestOrder = TestOrder::None.union(TestOrder::C).union(TEST_CONST_RW).difference(TestOrder::A);
+// Enabled | Mode=Turbo | Level=2
+const TEST_CONST_GROUP: TestGroups = TestGroups::from_value_retain(0xB1);
+const TEST_CONST_GROUP_FIELDS: (Option<TestGroupsMode>, u8) = (TEST_CONST_GROUP.mode(), TEST_CONST_GROUP.level());
+const TEST_CONST_CHECKS: [bool; 4] = [
+    TEST_CONST_RW.contains(TestOrder::A),
+    TEST_CONST_RW.contains_one(TestOrder::C),
+    TestOrder::None.is_empty(),
+    TestConstraints::try_from_value(0x03).is_err(),
+];
+
+#[test]
+fn test_const() {
+    assert_eq!(TEST_CONST_TABLE, [TEST_CONST_RW, TestOrder::B, TestOrder::A | TestOrder::B | TestOrder::C, TestOrder::C]);
+    assert_eq!(TEST_CONST_EDITED, TestOrder::B | TestOrder::C);
+    assert_eq!(TEST_CONST_GROUP_FIELDS, (Some(TestGroupsMode::Turbo), 2));
+    assert_eq!(TEST_CONST_CHECKS, [true, false, true, true]);
+    const VALID: Result<(), TestConstraintsViolationError> = TestConstraints::Signed.or(TestConstraints::Checksum).or(TestConstraints::Header).validate();
+    assert_eq!(VALID, Ok(()));
+    assert_eq!(TestOrder::A.or(TestOrder::C), TestOrder::A | TestOrder::C);
+}
@@ -0,0 +1,6 @@
+// compile errors reported by the macro (the expected messages and spans are in tests/ui/*.stderr)
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
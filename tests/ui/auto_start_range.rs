@@ -0,0 +1,10 @@
+use EnumBitFlags::EnumBitFlags;
+
+// `auto_start` must be the index of a bit of the selected representation
+#[EnumBitFlags(bits=16, auto_start=16)]
+enum AutoStartRange {
+    A,
+    B,
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+use EnumBitFlags::EnumBitFlags;
+
+// flags with #[cfg] attributes can not be used where their value is needed when the macro is expanded
+#[EnumBitFlags(bits=8)]
+enum CfgVariant {
+    Read = 1,
+    #[cfg(unix)]
+    Write = 2,
+    ReadWrite = Read | Write,
+    #[cfg(unix)]
+    WriteOnly = Write,
+    #[cfg(unix)]
+    Level = field(4..6),
+}
+
+// (the implications and constraints are only checked when the values are valid)
+#[EnumBitFlags(bits=8)]
+#[exclusive(Read, Write)]
+enum CfgImplies {
+    Read = 1,
+    #[cfg(unix)]
+    Write = 2,
+    #[cfg(windows)]
+    #[implies(Read)]
+    Execute = 4,
+    #[implies(Write)]
+    Delete = 8,
+}
+
+fn main() {}
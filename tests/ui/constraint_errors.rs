@@ -0,0 +1,19 @@
+use EnumBitFlags::EnumBitFlags;
+
+#[EnumBitFlags]
+#[requires(A)]
+enum TooFewFlags {
+    A = 1,
+    B = 2,
+}
+
+#[EnumBitFlags]
+#[exclusive(A, Unknown)]
+#[exclusive(A, AB)]
+enum ConstraintErrors {
+    A = 1,
+    B = 2,
+    AB = A | B,
+}
+
+fn main() {}
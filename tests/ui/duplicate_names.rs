@@ -0,0 +1,11 @@
+use EnumBitFlags::EnumBitFlags;
+
+#[EnumBitFlags]
+enum DuplicateNames {
+    Read = 1,
+    READ = 2,
+    Write = 4,
+    Write = 8,
+}
+
+fn main() {}
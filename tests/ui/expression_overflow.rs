@@ -0,0 +1,11 @@
+use EnumBitFlags::EnumBitFlags;
+
+#[EnumBitFlags(bits=8)]
+enum ExpressionOverflow {
+    A = 0x80 + 0x80,
+    B = 1 << 8,
+    C = 2 - 4,
+    D = 4 / 0,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+#![deny(deprecated)]
+use EnumBitFlags::EnumBitFlags;
+
+// the attributes of fields and groups are added to their getters and setters
+#[EnumBitFlags(bits=8)]
+enum FieldAttributes {
+    Valid = 1,
+    /// The old priority
+    #[deprecated(note = "use the flags instead")]
+    Priority = field(4..6),
+    #[deprecated]
+    Mode = group(6..8, Slow = 1, Fast = 2),
+}
+
+fn main() {
+    let mut f = FieldAttributes::Valid;
+    f.set_priority(2);
+    let _ = f.priority();
+    f.clear_mode();
+}
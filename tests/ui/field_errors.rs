@@ -0,0 +1,12 @@
+use EnumBitFlags::EnumBitFlags;
+
+#[EnumBitFlags(bits=16)]
+enum FieldErrors {
+    Ready = 0x10,
+    Level = field(2..6),
+    Speed = field(5..8),
+    Size = field(8..20),
+    Mode = group(10..12, Slow = 1, Fast = 1),
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use EnumBitFlags::EnumBitFlags;
+
+// the bounds of a field are checked before they are converted to bit indexes
+#[EnumBitFlags(bits=64)]
+enum FieldRange {
+    A = 1,
+    Truncated = field(0x100000004..0x100000008),
+    Overflow = field(0..=0xFFFFFFFF),
+    Inclusive = field(60..=64),
+    Empty = field(40..40),
+}
+
+fn main() {}
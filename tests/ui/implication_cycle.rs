@@ -0,0 +1,20 @@
+use EnumBitFlags::EnumBitFlags;
+
+#[EnumBitFlags]
+enum ImplicationCycle {
+    #[implies(B)]
+    A = 1,
+    #[implies(C)]
+    B = 2,
+    #[implies(A)]
+    C = 4,
+}
+
+#[EnumBitFlags]
+enum UnknownImplication {
+    A = 1,
+    #[implies(Missing)]
+    D = 8,
+}
+
+fn main() {}
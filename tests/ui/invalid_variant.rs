@@ -0,0 +1,11 @@
+use EnumBitFlags::EnumBitFlags;
+
+// a variant that can not be parsed is dropped and is not confused with the next ones
+#[EnumBitFlags(bits=8)]
+enum InvalidVariant {
+    A(u8),
+    B = 2,
+    C = A | B,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use EnumBitFlags::EnumBitFlags;
+
+// flags are associated constants, so their names can not be the same as a generated method or constant
+#[EnumBitFlags(bits=8)]
+enum MethodNames {
+    Read = 1,
+    all = 2,
+    iter = 4,
+    or = 8,
+    ALL_BITS = 0x10,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use EnumBitFlags::EnumBitFlags;
+
+// every problem is reported in the same compilation
+#[EnumBitFlags(bits=12, colour=red)]
+enum MultipleErrors {
+    A = 1,
+    B = "text",
+    C = A + Missing,
+    D = 1 << 40,
+    E = 1,
+}
+
+fn main() {}
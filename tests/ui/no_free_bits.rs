@@ -0,0 +1,10 @@
+use EnumBitFlags::EnumBitFlags;
+
+#[EnumBitFlags(bits=8, auto_start=6)]
+enum NoFreeBits {
+    A,
+    B,
+    C,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use EnumBitFlags::EnumBitFlags;
+
+#[EnumBitFlags(bits=128)]
+enum OversizedLiteral {
+    A = 0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF,
+}
+
+#[EnumBitFlags(bits=8)]
+enum ValueTooLarge {
+    A = 0x100,
+}
+
+fn main() {}
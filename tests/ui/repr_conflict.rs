@@ -0,0 +1,15 @@
+use EnumBitFlags::EnumBitFlags;
+
+#[EnumBitFlags(bits=32)]
+#[repr(u16)]
+enum ReprConflict {
+    A = 1,
+}
+
+#[EnumBitFlags]
+#[repr(u8, align(4))]
+enum ReprHint {
+    A = 1,
+}
+
+fn main() {}